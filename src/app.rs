@@ -1,8 +1,13 @@
-mod command;
+pub mod command;
+
+use std::path::PathBuf;
 
 use anyhow::Result;
 use clap::Parser;
 use command::Command;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
 
 #[derive(Debug, Parser)]
 #[command(
@@ -19,16 +24,29 @@ pub struct Args {
 
     #[clap(flatten)]
     config_args: ConfigArgs,
+
+    /// Which config file to use
+    ///
+    /// The config file sets the defaults of options, which can still be overridden on the command line.
+    /// Without this option, 'TRASHY_CONFIG' or '$XDG_CONFIG_HOME/trashy/config.toml' is used if it exists.
+    /// The options of the file can be overridden with 'TRASHY_' environment variables named after them,
+    /// like 'TRASHY_TIME=precise' or 'TRASHY_COLUMNS=index,size,path'.
+    /// Run 'trashy config' to see the effective configuration.
+    #[arg(long, value_name = "PATH", verbatim_doc_comment)]
+    config: Option<PathBuf>,
+
+    /// Don't read any config file
+    #[arg(long, conflicts_with = "config")]
+    no_config: bool,
 }
 
+/// How trash items are displayed.
 #[derive(Debug, Parser)]
 pub struct ConfigArgs {
     /// When to use colors
     ///
-    /// Declare when to use color for the pattern match output:
-    ///    'auto':      show colors if the output goes to an interactive console
-    ///    'never':     do not use colorized output
-    ///    'always':    always use colorized output,
+    /// Declare when to use color for the pattern match output.
+    /// The default can also be set with 'TRASHY_COLOR', or the 'NO_COLOR' and 'CLICOLOR_FORCE' conventions.
     #[arg(
         short = 'c',
         long = "color",
@@ -40,9 +58,7 @@ pub struct ConfigArgs {
 
     /// When to use time
     ///
-    /// Declare when to use time for the pattern match output:
-    ///    'precise':      use "%d/%m/%Y %H:%M" format for time output
-    ///    'imprecise':    use text representation for time output
+    /// Declare how to show times in the pattern match output.
     #[arg(
         long = "time",
         value_enum,
@@ -53,10 +69,7 @@ pub struct ConfigArgs {
 
     /// When to format as a table
     ///
-    /// Declare when to use color for the pattern match output:
-    ///    'auto':      format as a table if the output goes to an interactive console
-    ///    'never':     do not format as a table
-    ///    'always':    always format as a table
+    /// Declare when to format the pattern match output as a table.
     #[arg(
         short = 't',
         long = "table",
@@ -65,16 +78,48 @@ pub struct ConfigArgs {
         verbatim_doc_comment,
     )]
     pub table_status: Status,
+
+    /// Which columns to show
+    ///
+    /// Declare which columns to show and in which order, separated by commas.
+    /// Examples:
+    ///     --columns index,time,size,path
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_values_t = Column::DEFAULT,
+        verbatim_doc_comment,
+    )]
+    pub columns: Vec<Column>,
 }
 
-#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+impl Default for ConfigArgs {
+    /// The same options as on the command line without arguments.
+    fn default() -> ConfigArgs {
+        ConfigArgs {
+            color_status: Status::Auto,
+            time_display_mode: TimeDisplayMode::Imprecise,
+            table_status: Status::Auto,
+            columns: Column::DEFAULT.to_vec(),
+        }
+    }
+}
+
+/// When to use a feature like colors.
+#[derive(clap::ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
 pub enum Status {
+    /// Only if the output goes to an interactive console.
     Auto,
+    /// Always use it.
     Always,
+    /// Never use it.
     Never,
 }
 
 impl Status {
+    /// Whether the feature is used, where `is_atty` is whether the output goes to an interactive console.
     pub fn merge(&self, is_atty: bool) -> bool {
         use Status::*;
         match self {
@@ -85,15 +130,80 @@ impl Status {
     }
 }
 
-#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+/// How times are shown.
+#[derive(clap::ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
 pub enum TimeDisplayMode {
+    /// Use the "%d/%m/%Y %H:%M" format.
     Precise,
+    /// Use a text representation like "2 hours ago".
     Imprecise,
 }
 
+/// A column of the table shown by 'trashy list'.
+#[derive(clap::ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Column {
+    /// The index to use with '--ranges'.
+    Index,
+    /// The id of the trash item.
+    Id,
+    /// When the item was trashed, formatted according to '--time'.
+    Time,
+    /// When the item was trashed, always formatted precisely.
+    PreciseTime,
+    /// The size of the trashed file, including its contents.
+    Size,
+    /// The type of the trashed file.
+    Type,
+    /// The original path of the item.
+    #[value(alias = "original-path")]
+    #[serde(alias = "original-path")]
+    Path,
+    /// The original parent directory of the item.
+    #[value(alias = "original-parent")]
+    #[serde(alias = "original-parent")]
+    Parent,
+    /// The original file name of the item.
+    Name,
+    /// The trash directory the item is in.
+    Trash,
+    /// The path of the item's info file.
+    Info,
+}
+
+impl Column {
+    /// The columns shown without '--columns'.
+    pub const DEFAULT: [Column; 3] = [Column::Index, Column::Time, Column::Path];
+
+    /// The title of the column in the table header.
+    pub fn header(self) -> &'static str {
+        use Column::*;
+        match self {
+            Index => "i",
+            Id => "Id",
+            Time => "Time",
+            PreciseTime => "Precise Time",
+            Size => "Size",
+            Type => "Type",
+            Path => "Path",
+            Parent => "Parent",
+            Name => "Name",
+            Trash => "Trash",
+            Info => "Info",
+        }
+    }
+
+    /// Whether the column can get long enough that it should be truncated.
+    pub fn is_path(self) -> bool {
+        use Column::*;
+        matches!(self, Id | Path | Parent | Name | Trash | Info)
+    }
+}
+
 impl Args {
-    pub fn run(self) -> Result<()> {
-        self.command.run(&self.config_args)?;
+    pub fn run(self, config: &Config) -> Result<()> {
+        self.command.run(&self.config_args, config)?;
         Ok(())
     }
 }
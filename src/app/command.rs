@@ -1,14 +1,21 @@
+mod cat;
 mod completions;
+mod config;
 mod empty;
-mod list;
+pub mod list;
 mod manpage;
 pub mod put;
 mod restore;
-mod utils;
+mod show;
+mod stats;
+mod tui;
+pub mod utils;
 
 use anyhow::Result;
 use clap::Parser;
 
+use crate::config::Config;
+
 #[derive(Parser, Debug)]
 pub enum Command {
     /// List files
@@ -23,6 +30,21 @@ pub enum Command {
     /// Restore files
     Restore(restore::Args),
 
+    /// Show details about a trashed file without restoring it
+    Show(show::Args),
+
+    /// Print the contents of a trashed file without restoring it
+    Cat(cat::Args),
+
+    /// Show statistics about the trash
+    Stats(stats::Args),
+
+    /// Browse, restore and remove trashed files interactively
+    Tui(tui::Args),
+
+    /// Print the effective configuration
+    Config(config::Args),
+
     /// Generates completion for a shell
     Completions(completions::Args),
 
@@ -31,14 +53,19 @@ pub enum Command {
 }
 
 impl Command {
-    pub fn run(self, config_args: &super::ConfigArgs) -> Result<()> {
+    pub fn run(self, config_args: &super::ConfigArgs, config: &Config) -> Result<()> {
         use Command::*;
         match self {
             List(args) => args.run(config_args),
-            Put(args) => args.run(config_args),
+            Put(args) => args.run(config_args, config),
             Empty(args) => args.run(config_args),
             Restore(args) => args.run(config_args),
-            Completions(args) => args.run(),
+            Show(args) => args.run(config_args),
+            Cat(args) => args.run(config_args),
+            Stats(args) => args.run(config_args),
+            Tui(args) => args.run(config_args),
+            Config(args) => args.run(config),
+            Completions(args) => args.run(config),
             Manpage(args) => args.run(),
         }
     }
@@ -0,0 +1,38 @@
+use std::fs;
+use std::io::{self, Write};
+
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+
+use crate::{app, trash_item};
+
+use super::utils::ItemSelector;
+
+#[derive(Debug, Parser)]
+pub struct Args {
+    #[clap(flatten)]
+    selector: ItemSelector,
+}
+
+impl Args {
+    pub fn run(&self, _: &app::ConfigArgs) -> Result<()> {
+        let (_, item) = self.selector.select_one()?;
+        let path = match trash_item::payload_path(&item) {
+            Some(path) => path,
+            None => bail!("Reading trashed files is not supported on this platform"),
+        };
+        let mut stdout = io::stdout().lock();
+        if path.is_dir() {
+            let entries = trash_item::dir_entries(&path)
+                .with_context(|| format!("Failed to read directory `{}`", path.display()))?;
+            for entry in entries {
+                writeln!(stdout, "{entry}")?;
+            }
+        } else {
+            let mut file = fs::File::open(&path)
+                .with_context(|| format!("Failed to open `{}`", path.display()))?;
+            io::copy(&mut file, &mut stdout)?;
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 use std::io;
 
 use crate::app;
+use crate::config::Config;
 use anyhow::Result;
 use clap::{CommandFactory, Parser};
 
@@ -12,8 +13,9 @@ pub struct Args {
 }
 
 impl Args {
-    pub fn run(&self) -> Result<()> {
-        clap_complete::generate(self.shell, &mut app::Args::command(), "trashy", &mut io::stdout());
+    pub fn run(&self, config: &Config) -> Result<()> {
+        let mut cmd = config.apply(app::Args::command());
+        clap_complete::generate(self.shell, &mut cmd, "trashy", &mut io::stdout());
         Ok(())
     }
 }
@@ -0,0 +1,35 @@
+use anyhow::{Context, Result};
+use clap::Parser;
+
+use crate::config::{self, Config};
+
+#[derive(Debug, Parser)]
+pub struct Args {
+    /// Only print the path of the config file
+    ///
+    /// This is the file that was read, or the file that would be read if it existed.
+    #[arg(long)]
+    path: bool,
+}
+
+impl Args {
+    pub fn run(&self, config: &Config) -> Result<()> {
+        let path = config.path.clone().or_else(config::default_path);
+        if self.path {
+            match path {
+                Some(path) => println!("{}", path.display()),
+                None => println!("No config file location could be determined"),
+            }
+            return Ok(());
+        }
+        match &config.path {
+            Some(path) => println!("# Read from {}", path.display()),
+            None => println!("# No config file was read"),
+        }
+        if !config.env_vars.is_empty() {
+            println!("# Overridden by {}", config.env_vars.join(", "));
+        }
+        print!("{}", toml::to_string(&config.effective()).context("Failed to format config")?);
+        Ok(())
+    }
+}
@@ -6,7 +6,7 @@ use crate::{app, trash_item::MaybeIndexedTrashItems};
 
 use super::{
     list,
-    utils::{Force, Ranges},
+    utils::{Force, Interactive, Pick, Ranges},
 };
 
 #[derive(Parser, Debug)]
@@ -15,12 +15,20 @@ pub struct Args {
     query_args: list::QueryArgs,
 
     /// Empty all files
-    #[arg(long, conflicts_with_all = list::QueryArgs::CONFLICTS)]
+    #[arg(
+        long,
+        conflicts_with = "ranges",
+        conflicts_with = "pick",
+        conflicts_with_all = list::QueryArgs::CONFLICTS
+    )]
     all: bool,
 
     #[clap(flatten)]
     ranges: Ranges,
 
+    #[clap(flatten)]
+    pick: Pick,
+
     #[clap(flatten)]
     force: Force,
 }
@@ -29,20 +37,37 @@ impl Args {
     pub fn run(&self, config_args: &app::ConfigArgs) -> Result<()> {
         let empty: Box<dyn Fn(_) -> _> = if self.force.force {
             Box::new(empty)
+        } else if self.force.interactive == Interactive::Each {
+            Box::new(|items| {
+                super::utils::on_each_item_with_prompt(items, config_args, "emptied", empty)
+            })
         } else {
             Box::new(|items| {
-                super::utils::on_items_with_prompt(items, config_args, "emptied", empty)
+                super::utils::on_items_with_typed_prompt(items, config_args, "emptied", empty)
             })
         };
 
         if self.all {
             empty(MaybeIndexedTrashItems(Left(list::list_only()?)))?
-        } else if self.ranges.ranges.is_empty() {
-            empty(MaybeIndexedTrashItems(Left(self.query_args.list(true)?)))?
+        } else if self.pick.pick {
+            empty(MaybeIndexedTrashItems(Right(Pick::pick(self.query_args.list()?, config_args)?)))?
+        } else if !self.ranges.ranges.is_empty() {
+            empty(MaybeIndexedTrashItems(Right(self.query_args.list_ranged(&self.ranges)?)))?
         } else {
-            empty(MaybeIndexedTrashItems(Right(
-                self.query_args.list_ranged(true, self.ranges.parse()?)?,
-            )))?
+            let filters = self.query_args.filter_args.to_filters()?;
+            let no_filters = filters.is_empty();
+            let items = list::list(
+                self.query_args.sort,
+                self.query_args.rev,
+                self.query_args.max,
+                filters,
+            )?;
+            if no_filters {
+                let items = super::utils::prompt_ranges(&items, config_args, "empty")?;
+                empty(MaybeIndexedTrashItems(Right(items)))?
+            } else {
+                empty(MaybeIndexedTrashItems(Left(items)))?
+            }
         }
         Ok(())
     }
@@ -1,4 +1,3 @@
-use std::borrow::Cow;
 use std::io::Write;
 use std::num::NonZeroU32;
 use std::{
@@ -8,13 +7,16 @@ use std::{
 
 use chrono::{Local, TimeZone};
 use clap::Parser;
-use tabled::{width::Truncate, Table, Tabled};
+use serde::{Deserialize, Serialize};
+use tabled::papergrid::util::string_width_multiline;
+use tabled::{builder::Builder, width::Truncate, Table};
 
 use anyhow::{bail, Context, Result};
 use trash::TrashItem;
 
-use crate::app::TimeDisplayMode;
+use crate::app::{Column, TimeDisplayMode};
 use crate::filter::Filters;
+use crate::trash_item::{self, files_path_from_info_path};
 use crate::{
     app,
     filter::FilterArgs,
@@ -22,15 +24,32 @@ use crate::{
     utils::{self, swap},
 };
 
+use super::utils::Ranges;
+
+mod tree;
+
 #[derive(Parser, Debug)]
 pub struct Args {
     #[clap(flatten)]
     query_args: QueryArgs,
+
+    /// Show trash items as a tree
+    ///
+    /// Group trash items by the directory they were trashed from.
+    /// Each directory shows how many items were trashed from it and their total size.
+    /// The numbers in brackets are the indices that can be used with '--ranges'.
+    #[arg(long, verbatim_doc_comment)]
+    tree: bool,
 }
 
 impl Args {
     pub fn run(&self, config_args: &app::ConfigArgs) -> Result<()> {
-        display_items(&self.query_args.list(false)?, config_args)?;
+        let items = self.query_args.list()?;
+        if self.tree {
+            tree::display_tree(&items, config_args)?;
+        } else {
+            display_items(&items, config_args)?;
+        }
         Ok(())
     }
 }
@@ -50,6 +69,14 @@ pub struct QueryArgs {
     #[arg(long, verbatim_doc_comment)]
     pub rev: bool,
 
+    /// How to sort trash items
+    ///
+    /// This will also affect 'empty' or 'restore' if used in either command.
+    /// Examples:
+    /// 'trashy list -m fuzzy mnrs --sort score' will show the best match at the bottom.
+    #[arg(long, value_enum, default_value_t = SortBy::Time, verbatim_doc_comment)]
+    pub sort: SortBy,
+
     /// Show 'n' maximum trash items
     ///
     /// This will also affect 'empty' or 'restore' if used in either command.
@@ -64,45 +91,81 @@ impl QueryArgs {
     pub const CONFLICTS: &'static [&'static str] = &[
         "before",
         "within",
+        "deleted",
+        "now",
         "glob",
         "regex",
         "exact",
         "substring",
+        "fuzzy",
+        "fuzzy_threshold",
         "patterns",
         "match",
+        "match_on",
+        "ignore_case",
+        "case_sensitive",
         "rev",
+        "sort",
         "max",
         "directories",
+        "max_depth",
+        "direct",
+        "file_types",
+        "size",
+        "ext",
+        "owner",
+        "perm",
+        "modified_before",
+        "modified_after",
+        "query",
+        "not",
+        "or",
     ];
 
-    pub fn list(&self, non_empty: bool) -> Result<Vec<TrashItem>> {
-        let filters = self.filter_args.to_filters()?;
-        if non_empty && filters.is_empty() {
-            bail!("Must match something");
-        }
-        list(self.rev, self.max, filters)
+    pub fn list(&self) -> Result<Vec<TrashItem>> {
+        list(self.sort, self.rev, self.max, self.filter_args.to_filters()?)
     }
 
-    pub fn list_ranged(&self, non_empty: bool, ranges: RangeSet) -> Result<Vec<(u32, TrashItem)>> {
-        let filters = self.filter_args.to_filters()?;
-        if non_empty && filters.is_empty() {
-            bail!("Must match something");
-        }
-        list_ranged(self.rev, self.max, filters, ranges)
+    pub fn list_ranged(&self, ranges: &Ranges) -> Result<Vec<(u32, TrashItem)>> {
+        list_ranged(self.sort, self.rev, self.max, self.filter_args.to_filters()?, ranges)
     }
 }
 
+/// How trash items are sorted.
+#[derive(clap::ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SortBy {
+    /// By when the item was trashed, newest first
+    Time,
+    /// By the original path
+    Path,
+    /// By how well the item matches fuzzy patterns, best first
+    Score,
+}
+
+/// Lists all trash items, newest first.
 pub fn list_only() -> Result<Vec<TrashItem>> {
     let mut items = trash::os_limited::list()?;
     items.sort_by_key(|item| cmp::Reverse(item.time_deleted));
     Ok(items)
 }
 
-pub fn list(rev: bool, max: Option<NonZeroU32>, filters: Filters) -> Result<Vec<TrashItem>> {
-    Ok(process_items(rev, max, filters, trash::os_limited::list()?))
+/// Lists the trash items matching `filters`, like `trashy list` with the same options.
+pub fn list(
+    sort: SortBy,
+    rev: bool,
+    max: Option<NonZeroU32>,
+    filters: Filters,
+) -> Result<Vec<TrashItem>> {
+    Ok(process_items(sort, rev, max, filters, trash::os_limited::list()?))
 }
 
+/// Filters, sorts and limits `items` the way `trashy list` does.
+///
+/// Items are sorted newest first, then by `sort`, then reversed if `rev` is set,
+/// and finally cut off after `max` items. The indices of the result are the ones used by ranges.
 pub fn process_items(
+    sort: SortBy,
     rev: bool,
     max: Option<NonZeroU32>,
     filters: Filters,
@@ -113,10 +176,14 @@ pub fn process_items(
     } else {
         items
     };
+    items.sort_by_key(|item| cmp::Reverse(item.time_deleted));
+    match sort {
+        SortBy::Time => (),
+        SortBy::Path => items.sort_by_cached_key(|item| item.original_path()),
+        SortBy::Score => items.sort_by_cached_key(|item| cmp::Reverse(filters.score(item))),
+    }
     if rev {
-        items.sort_by_key(|item| item.time_deleted);
-    } else {
-        items.sort_by_key(|item| cmp::Reverse(item.time_deleted));
+        items.reverse();
     }
     match max {
         Some(n) => items.into_iter().take(n.get() as usize).collect(),
@@ -125,36 +192,35 @@ pub fn process_items(
 }
 
 pub fn list_ranged(
+    sort: SortBy,
     rev: bool,
     max: Option<NonZeroU32>,
     filters: Filters,
-    ranges: RangeSet,
+    ranges: &Ranges,
 ) -> Result<Vec<(u32, TrashItem)>> {
-    let items = list(rev, max, filters)?;
-    filter_by_ranges(&items, ranges)
+    let items = list(sort, rev, max, filters)?;
+    filter_by_ranges(&items, ranges.parse(items.len())?)
 }
 
+/// Picks the items in `ranges` out of `items`, together with their indices.
 pub fn filter_by_ranges(items: &[TrashItem], ranges: RangeSet) -> Result<Vec<(u32, TrashItem)>> {
     let mut new_items = Vec::new();
     for range in ranges {
-        if range.start() as usize > items.len() || range.end() as usize > items.len() {
+        if range.end as usize > items.len() {
             bail!("Range is out of bounds");
         }
-        new_items.extend(
-            items[range.to_std()]
-                .iter()
-                .map(utils::clone_trash_item)
-                .zip(range.into_iter())
-                .map(swap),
-        );
+        let slice = &items[range.start as usize..range.end as usize];
+        new_items.extend(slice.iter().map(utils::clone_trash_item).zip(range).map(swap));
     }
     Ok(new_items)
 }
 
-pub fn display_items<'a>(items: &[TrashItem], config_args: &app::ConfigArgs) -> Result<()> {
+/// Prints `items` to stdout with their indices, like `trashy list` does.
+pub fn display_items(items: &[TrashItem], config_args: &app::ConfigArgs) -> Result<()> {
     display_indexed_items(items.iter().zip(0..items.len() as u32).map(swap), config_args)
 }
 
+/// Prints `items` to stdout with the given indices, where the last item is printed first.
 pub fn display_indexed_items<'a>(
     items: impl DoubleEndedIterator<Item = (u32, &'a TrashItem)> + ExactSizeIterator,
     config_args: &app::ConfigArgs,
@@ -165,6 +231,7 @@ pub fn display_indexed_items<'a>(
         config_args.color_status.merge(is_atty),
         config_args.table_status.merge(is_atty),
         config_args.time_display_mode,
+        &config_args.columns,
         Path::new(""),
     )
 }
@@ -174,55 +241,150 @@ fn display_indexed_items_with<'a>(
     use_color: bool,
     use_table: bool,
     time_display_mode: TimeDisplayMode,
+    columns: &[Column],
     base: &Path,
 ) -> Result<()> {
     if items.len() == 0 {
         return Ok(());
     }
-    let table = indexed_items_to_table(items, use_color, use_table, time_display_mode, base)?;
+    let table =
+        indexed_items_to_table(items, use_color, use_table, time_display_mode, columns, base)?;
     writeln!(io::stdout(), "{table}").context("Printing table")?;
     Ok(())
 }
 
+/// Formats `items` like [`display_indexed_items`], with paths shown relative to `base`.
 pub fn indexed_items_to_table<'a>(
     items: impl DoubleEndedIterator<Item = (u32, &'a TrashItem)>,
     use_color: bool,
     use_table: bool,
     time_display_mode: TimeDisplayMode,
+    columns: &[Column],
     base: &Path,
 ) -> Result<Table> {
-    let mut failed = 0; // 'failed' does not seem to be read anywhere except 197 line
-
+    // items whose path could not be displayed are skipped
     // this isn't actually needless since we need to reverse the items, which can't be done with a single-ended iterator
-    let items = items
-        .filter_map(|(i, item)| match display_item(item, use_color, time_display_mode, base) {
-            Ok(s) => Some(TrashItemDisplay { i, time: s.0, path: s.1 }),
-            Err(_) => {
-                failed += 1;
-                None
-            }
+    let rows: Vec<_> = items
+        .filter_map(|(i, item)| {
+            display_item_columns(i, item, use_color, time_display_mode, columns, base).ok()
         })
-        .rev();
-    let mut table = Table::builder(items);
-    if !use_table {
-        table.remove_columns();
-    };
+        .rev()
+        .collect();
+    let terminal_width =
+        terminal_size::terminal_size().map(|(terminal_size::Width(width), _)| width as usize);
+    Ok(rows_to_table(rows, columns, use_table, terminal_width))
+}
+
+/// Builds the table of [`indexed_items_to_table`] from its `rows`,
+/// truncating the path columns if the table is wider than `terminal_width`.
+fn rows_to_table(
+    rows: Vec<Vec<String>>,
+    columns: &[Column],
+    use_table: bool,
+    terminal_width: Option<usize>,
+) -> Table {
+    let mut widths: Vec<_> = columns
+        .iter()
+        .map(|column| if use_table { string_width_multiline(column.header()) } else { 0 })
+        .collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(string_width_multiline(cell));
+        }
+    }
+    let mut builder = Builder::default();
+    for row in rows {
+        builder.add_record(row);
+    }
+    if use_table {
+        builder.set_columns(columns.iter().map(|column| column.header()));
+    }
     use tabled::{object::Segment, Alignment, Modify};
-    let mut table = table.build();
+    let mut table = builder.build();
     table.with(Modify::new(Segment::all()).with(Alignment::left()));
-    if let Some((terminal_size::Width(width), _)) = terminal_size::terminal_size() {
-        let width = width as usize;
-        table
-            .with(Modify::new(Segment::new(.., 2..)).with(Truncate::new(width - 30).suffix("...")));
-    }
     if use_table {
         table.with(tabled::Style::rounded());
     } else {
         table.with(tabled::Style::empty());
     }
-    Ok(table)
+    if let Some(terminal_width) = terminal_width {
+        let total_width = table.total_width();
+        if total_width > terminal_width {
+            let (path_columns, path_widths): (Vec<_>, Vec<_>) = (0..columns.len())
+                .filter(|&j| columns[j].is_path())
+                .map(|j| (j, widths[j]))
+                .unzip();
+            // the space taken by borders, padding and the columns that aren't truncated
+            let fixed_width = total_width.saturating_sub(path_widths.iter().sum());
+            let available = terminal_width.saturating_sub(fixed_width);
+            for (j, width) in path_columns.into_iter().zip(split_width(&path_widths, available)) {
+                table.with(
+                    Modify::new(Segment::new(.., j..j + 1))
+                        .with(Truncate::new(width).suffix("...")),
+                );
+            }
+        }
+    }
+    table
 }
 
+/// Splits `available` between columns that want to be `widths` wide.
+///
+/// Columns that fit into an equal share keep their width and leave the rest to the wider ones,
+/// but no column gets narrower than [`MIN_PATH_WIDTH`].
+fn split_width(widths: &[usize], available: usize) -> Vec<usize> {
+    let mut order: Vec<_> = (0..widths.len()).collect();
+    order.sort_by_key(|&j| widths[j]);
+    let mut split = vec![0; widths.len()];
+    let mut available = available;
+    for (k, &j) in order.iter().enumerate() {
+        let share = available / (widths.len() - k);
+        split[j] = widths[j].min(share).max(MIN_PATH_WIDTH.min(widths[j]));
+        available = available.saturating_sub(split[j]);
+    }
+    split
+}
+
+/// The narrowest a path column is truncated to, so that at least a bit of it is readable.
+const MIN_PATH_WIDTH: usize = 8;
+
+pub fn display_item_columns(
+    i: u32,
+    item: &TrashItem,
+    color: bool,
+    time_display_mode: TimeDisplayMode,
+    columns: &[Column],
+    base: &Path,
+) -> Result<Vec<String>> {
+    let display_opt_path =
+        |path: Option<PathBuf>| path.map(|p| utils::path::display(&p)).unwrap_or_default();
+    columns
+        .iter()
+        .map(|column| {
+            Ok(match column {
+                Column::Index => i.to_string(),
+                Column::Id => item.id.to_string_lossy().into_owned(),
+                Column::Time => display_item_date(item, time_display_mode),
+                Column::PreciseTime => display_item_date(item, TimeDisplayMode::Precise),
+                Column::Size => {
+                    trash_item::payload_size(item).map(utils::display_size).unwrap_or_default()
+                }
+                Column::Type => trash_item::payload_metadata(item)
+                    .map(|meta| display_file_type(meta.file_type()).to_string())
+                    .unwrap_or_default(),
+                Column::Path => display_item(item, color, time_display_mode, base)?.1,
+                Column::Parent => utils::path::display(
+                    item.original_parent.strip_prefix(base).unwrap_or(&item.original_parent),
+                ),
+                Column::Name => item.name.clone(),
+                Column::Trash => display_opt_path(trash_item::trash_dir(item)),
+                Column::Info => display_opt_path(trash_item::info_path(item)),
+            })
+        })
+        .collect()
+}
+
+/// Formats when `item` was trashed and its original path relative to `base`, colored like `ls` if `color` is set.
 pub fn display_item(
     item: &TrashItem,
     color: bool,
@@ -239,21 +401,15 @@ pub fn display_item(
     Ok((display_item_date(item, time_display_mode), displayed_path))
 }
 
-pub struct TrashItemDisplay {
-    i: u32,
-    time: String,
-    path: String,
-}
-
-impl Tabled for TrashItemDisplay {
-    const LENGTH: usize = 3;
-
-    fn fields(&self) -> Vec<Cow<'_, str>> {
-        vec![self.i.to_string().into(), self.time.clone().into(), self.path.clone().into()]
-    }
-
-    fn headers() -> Vec<Cow<'static, str>> {
-        vec!["i".into(), "Time".into(), "Path".into()]
+pub fn display_file_type(file_type: fs::FileType) -> &'static str {
+    if file_type.is_dir() {
+        "dir"
+    } else if file_type.is_symlink() {
+        "symlink"
+    } else if file_type.is_file() {
+        "file"
+    } else {
+        "other"
     }
 }
 
@@ -271,11 +427,12 @@ pub fn item_lscolors(item: &TrashItem) -> Result<Option<lscolors::Style>> {
     }
 }
 
+/// Formats when `item` was trashed.
 pub fn display_item_date(item: &TrashItem, time_display_mode: TimeDisplayMode) -> String {
-    let datetime = Local.timestamp(item.time_deleted, 0);
+    let datetime = Local.timestamp_opt(item.time_deleted, 0).unwrap();
     match time_display_mode {
         TimeDisplayMode::Precise => {
-            format!("{}", Local.timestamp(item.time_deleted, 0).format("%d/%m/%Y %H:%M"))
+            format!("{}", datetime.format("%d/%m/%Y %H:%M"))
         }
         TimeDisplayMode::Imprecise => {
             let humantime = chrono_humanize::HumanTime::from(datetime);
@@ -284,10 +441,32 @@ pub fn display_item_date(item: &TrashItem, time_display_mode: TimeDisplayMode) -
     }
 }
 
-pub fn files_path_from_info_path(info_path: &Path) -> PathBuf {
-    let file_name = Path::new(info_path.file_name().unwrap()).with_extension("");
-    let mut files_path =
-        (|| Some(info_path.parent()?.parent()?.join("files")))().expect("Invalid info_path");
-    files_path.push(file_name);
-    files_path
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_width() {
+        assert_eq!(super::split_width(&[10, 20], 40), [10, 20]);
+        assert_eq!(super::split_width(&[10, 50], 40), [10, 30]);
+        assert_eq!(super::split_width(&[50, 10, 50], 40), [15, 10, 15]);
+        assert_eq!(super::split_width(&[50, 50], 41), [20, 21]);
+        assert_eq!(super::split_width(&[50, 5], 4), [MIN_PATH_WIDTH, 5]);
+        assert!(super::split_width(&[], 40).is_empty());
+    }
+
+    #[test]
+    fn multi_line_paths() {
+        let columns = [Column::Index, Column::Path, Column::Name];
+        let row = |i: u32, path: &str, name: &str| vec![i.to_string(), path.into(), name.into()];
+        let rows = vec![
+            row(0, "/home/user/a file with a long name\nand a second line.txt", "a\nb"),
+            row(1, "/home/user/projects/trashy/src/main.rs", "main.rs"),
+        ];
+        let table = rows_to_table(rows, &columns, true, Some(50)).to_string();
+        let lines: Vec<_> = table.lines().collect();
+        assert!(lines.iter().all(|line| string_width_multiline(line) == 50), "{table}");
+        // the path column gets the width the name column doesn't need, not the minimum
+        assert!(table.contains("/home/user/projects/trashy/sr..."), "{table}");
+    }
 }
@@ -0,0 +1,185 @@
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use trash::TrashItem;
+
+use crate::app::{self, TimeDisplayMode};
+use crate::{trash_item, utils};
+
+/// A trie of the original paths of trash items.
+#[derive(Default)]
+struct Node<'a> {
+    /// Items whose original path ends exactly at this node.
+    items: Vec<(u32, &'a TrashItem, Option<u64>)>,
+    children: BTreeMap<OsString, Node<'a>>,
+    /// Number of items at or below this node.
+    count: usize,
+    /// Total size of items at or below this node.
+    size: u64,
+}
+
+impl<'a> Node<'a> {
+    fn insert(&mut self, i: u32, item: &'a TrashItem) {
+        let size = trash_item::payload_size(item);
+        let path = item.original_path();
+        let mut node = self;
+        node.count += 1;
+        node.size += size.unwrap_or(0);
+        for component in path.components() {
+            node = node.children.entry(component.as_os_str().to_os_string()).or_default();
+            node.count += 1;
+            node.size += size.unwrap_or(0);
+        }
+        node.items.push((i, item, size));
+    }
+
+    /// Follows chains of directories that only have a single child and no items.
+    fn collapse(&self, mut name: PathBuf) -> (PathBuf, &Self) {
+        let mut node = self;
+        while node.items.is_empty() && node.children.len() == 1 {
+            let (child_name, child) = node.children.iter().next().unwrap();
+            name.push(child_name);
+            node = child;
+        }
+        (name, node)
+    }
+}
+
+struct TreeDisplay {
+    use_color: bool,
+    time_display_mode: TimeDisplayMode,
+}
+
+impl TreeDisplay {
+    fn write_node(
+        &self,
+        out: &mut impl Write,
+        name: PathBuf,
+        node: &Node,
+        prefix: &str,
+        connector: &str,
+    ) -> io::Result<()> {
+        let (name, node) = node.collapse(name);
+        let name = utils::path::display(&name);
+        if node.children.is_empty() {
+            for (i, item, size) in &node.items {
+                writeln!(out, "{prefix}{connector}{}", self.item_line(*i, item, *size, &name))?;
+            }
+            return Ok(());
+        }
+        let indices = node.items.iter().map(|(i, _, _)| format!("[{i}] ")).collect::<String>();
+        let plural = if node.count == 1 { "" } else { "s" };
+        // The root directory already ends with a separator.
+        let slash = if name.ends_with('/') { "" } else { "/" };
+        writeln!(
+            out,
+            "{prefix}{connector}{indices}{name}{slash} ({} item{plural}, {})",
+            node.count,
+            utils::display_size(node.size)
+        )?;
+        let child_prefix = match connector {
+            "" => prefix.to_string(),
+            "└── " => format!("{prefix}    "),
+            _ => format!("{prefix}│   "),
+        };
+        let len = node.children.len();
+        for (j, (child_name, child)) in node.children.iter().enumerate() {
+            let connector = if j + 1 == len { "└── " } else { "├── " };
+            self.write_node(out, PathBuf::from(child_name), child, &child_prefix, connector)?;
+        }
+        Ok(())
+    }
+
+    fn item_line(&self, i: u32, item: &TrashItem, size: Option<u64>, name: &str) -> String {
+        let mut name = name.to_string();
+        if self.use_color {
+            if let Ok(Some(style)) = super::item_lscolors(item) {
+                name = style.to_ansi_term_style().paint(name).to_string();
+            }
+        }
+        let time = super::display_item_date(item, self.time_display_mode);
+        match size {
+            Some(size) => format!("[{i}] {name} ({time}, {})", utils::display_size(size)),
+            None => format!("[{i}] {name} ({time})"),
+        }
+    }
+}
+
+pub fn display_tree(items: &[TrashItem], config_args: &app::ConfigArgs) -> Result<()> {
+    let mut root = Node::default();
+    for (i, item) in items.iter().enumerate() {
+        root.insert(i as u32, item);
+    }
+    let display = TreeDisplay {
+        use_color: config_args.color_status.merge(atty::is(atty::Stream::Stdout)),
+        time_display_mode: config_args.time_display_mode,
+    };
+    let mut stdout = io::stdout().lock();
+    for (name, node) in &root.children {
+        display
+            .write_node(&mut stdout, PathBuf::from(name), node, "", "")
+            .context("Printing tree")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsStr;
+    use std::path::Path;
+
+    use super::*;
+
+    #[test]
+    fn render() {
+        let item = |path: &str| {
+            let path = PathBuf::from(path);
+            TrashItem {
+                id: "/nonexistent/info/item.trashinfo".into(),
+                name: path.file_name().unwrap().to_string_lossy().into_owned(),
+                original_parent: path.parent().unwrap().to_path_buf(),
+                time_deleted: 1_700_000_000,
+            }
+        };
+        let items = [
+            item("/home/u/a.txt"),
+            item("/home/u/docs"),
+            item("/home/u/docs/x.md"),
+            item("/home/u/docs/y.md"),
+            item("/home/u/deep/one/two/z.rs"),
+            item("/tmp/t"),
+        ];
+        let mut root = Node::default();
+        for (i, item) in items.iter().enumerate() {
+            root.insert(i as u32, item);
+        }
+        let (name, node) = root.children[OsStr::new("/")].children[OsStr::new("home")].children
+            [OsStr::new("u")]
+        .children[OsStr::new("deep")]
+        .collapse(PathBuf::from("deep"));
+        assert_eq!(name, Path::new("deep/one/two/z.rs"));
+        assert_eq!((node.count, node.items.len()), (1, 1));
+
+        let display = TreeDisplay { use_color: false, time_display_mode: TimeDisplayMode::Precise };
+        let mut out = Vec::new();
+        for (name, node) in &root.children {
+            display.write_node(&mut out, PathBuf::from(name), node, "", "").unwrap();
+        }
+        let time = super::super::display_item_date(&items[0], TimeDisplayMode::Precise);
+        let expected = format!(
+            "/ (6 items, 0B)
+├── home/u/ (5 items, 0B)
+│   ├── [0] a.txt ({time})
+│   ├── [4] deep/one/two/z.rs ({time})
+│   └── [1] docs/ (3 items, 0B)
+│       ├── [2] x.md ({time})
+│       └── [3] y.md ({time})
+└── [5] tmp/t ({time})
+"
+        );
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+    }
+}
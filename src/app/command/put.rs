@@ -4,6 +4,7 @@ use anyhow::{bail, Result};
 use clap::Parser;
 
 use crate::app;
+use crate::config::Config;
 
 #[derive(Parser, Debug, PartialEq)]
 pub struct PutArgs {
@@ -12,11 +13,14 @@ pub struct PutArgs {
 }
 
 impl PutArgs {
-    pub fn run(&self, _: &app::ConfigArgs) -> Result<()> {
+    pub fn run(&self, _: &app::ConfigArgs, config: &Config) -> Result<()> {
         let paths = &self.paths;
         if paths.is_empty() {
             bail!("No paths were specified to trash");
         }
+        for path in paths {
+            config.ensure_unprotected(path)?;
+        }
         Ok(trash::delete_all(paths)?)
     }
 }
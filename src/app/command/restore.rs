@@ -1,6 +1,6 @@
 use anyhow::Result;
 
-use crate::{range_syntax, trash_item::MaybeIndexedTrashItems};
+use crate::trash_item::MaybeIndexedTrashItems;
 use clap::Parser;
 use either::Either::*;
 
@@ -8,7 +8,7 @@ use crate::app;
 
 use super::{
     list,
-    utils::{Force, Ranges},
+    utils::{Force, Interactive, Pick, Ranges},
 };
 
 #[derive(Debug, Parser)]
@@ -20,6 +20,9 @@ pub struct Args {
     #[clap(flatten)]
     ranges: Ranges,
 
+    #[clap(flatten)]
+    pick: Pick,
+
     #[clap(flatten)]
     force: Force,
 }
@@ -28,6 +31,10 @@ impl Args {
     pub fn run(&self, config_args: &app::ConfigArgs) -> Result<()> {
         let restore: Box<dyn Fn(_) -> _> = if self.force.force {
             Box::new(restore)
+        } else if self.force.interactive == Interactive::Each {
+            Box::new(|items| {
+                super::utils::on_each_item_with_prompt(items, config_args, "restored", restore)
+            })
         } else {
             Box::new(|items| {
                 super::utils::on_items_with_prompt(items, config_args, "restored", restore)
@@ -35,27 +42,40 @@ impl Args {
         };
 
         let filters = self.query_args.filter_args.to_filters()?;
-        if filters.is_empty() && self.ranges.ranges.is_empty() {
-            let items = list::list(self.query_args.rev, self.query_args.max, filters)?;
-            list::display_items(&items, config_args)?;
-            let ranges =
-                dialoguer::Input::<String>::new().with_prompt("restore ranges").interact_text()?;
-            restore(MaybeIndexedTrashItems(Right(list::filter_by_ranges(
+        if self.pick.pick {
+            let items = list::list(
+                self.query_args.sort,
+                self.query_args.rev,
+                self.query_args.max,
+                filters,
+            )?;
+            restore(MaybeIndexedTrashItems(Right(Pick::pick(items, config_args)?)))?
+        } else if filters.is_empty() && self.ranges.ranges.is_empty() {
+            let items = list::list(
+                self.query_args.sort,
+                self.query_args.rev,
+                self.query_args.max,
+                filters,
+            )?;
+            restore(MaybeIndexedTrashItems(Right(super::utils::prompt_ranges(
                 &items,
-                range_syntax::parse_range_set(&ranges)?,
+                config_args,
+                "restore",
             )?)))?
         } else if self.ranges.ranges.is_empty() {
             restore(MaybeIndexedTrashItems(Left(list::list(
+                self.query_args.sort,
                 self.query_args.rev,
                 self.query_args.max,
                 filters,
             )?)))?
         } else {
             restore(MaybeIndexedTrashItems(Right(list::list_ranged(
+                self.query_args.sort,
                 self.query_args.rev,
                 self.query_args.max,
                 filters,
-                self.ranges.parse()?,
+                &self.ranges,
             )?)))?;
         }
         Ok(())
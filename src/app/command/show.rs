@@ -0,0 +1,119 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, TimeZone};
+use clap::Parser;
+use trash::TrashItem;
+
+use crate::app::{self, TimeDisplayMode};
+use crate::{trash_item, utils};
+
+use super::{list, utils::ItemSelector};
+
+#[derive(Debug, Parser)]
+pub struct Args {
+    #[clap(flatten)]
+    selector: ItemSelector,
+}
+
+impl Args {
+    pub fn run(&self, _: &app::ConfigArgs) -> Result<()> {
+        let mut stdout = io::stdout().lock();
+        for (j, (i, item)) in self.selector.select()?.iter().enumerate() {
+            if j > 0 {
+                writeln!(stdout)?;
+            }
+            write!(stdout, "{}", display_details(*i, item)).context("Printing details")?;
+        }
+        Ok(())
+    }
+}
+
+pub fn display_details(i: u32, item: &TrashItem) -> String {
+    let mut fields = vec![
+        ("Index", i.to_string()),
+        ("Name", item.name.clone()),
+        ("Original path", utils::path::display(&item.original_path())),
+        (
+            "Deleted",
+            format!(
+                "{} ({})",
+                display_datetime(Local.timestamp_opt(item.time_deleted, 0).unwrap()),
+                list::display_item_date(item, TimeDisplayMode::Imprecise)
+            ),
+        ),
+    ];
+    let info_path = trash_item::info_path(item);
+    let payload_path = trash_item::payload_path(item);
+    fields.push(("Info file", display_opt_path(info_path.as_deref())));
+    fields.push(("Payload", display_opt_path(payload_path.as_deref())));
+    match payload_path.as_deref().and_then(|path| Some((path, fs::symlink_metadata(path).ok()?))) {
+        Some((path, meta)) => {
+            fields.push(("Type", list::display_file_type(meta.file_type()).to_string()));
+            if let Ok(size) = trash_item::dir_size(path) {
+                fields.push(("Size", format!("{} ({size} bytes)", utils::display_size(size))));
+            }
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                let mode = meta.permissions().mode();
+                fields.push(("Mode", format!("{} ({:o})", display_mode(mode), mode & 0o7777)));
+            }
+            if let Ok(modified) = meta.modified() {
+                fields.push(("Modified", display_datetime(DateTime::<Local>::from(modified))));
+            }
+            if meta.is_dir() {
+                if let Ok(count) = trash_item::count_entries(path) {
+                    fields.push(("Contains", format!("{count} entries")));
+                }
+            } else if meta.file_type().is_symlink() {
+                if let Ok(target) = fs::read_link(path) {
+                    fields.push(("Target", utils::path::display(&target)));
+                }
+            }
+        }
+        None if payload_path.is_some() => fields.push(("Type", "missing".to_string())),
+        None => (),
+    }
+    fields.push(("Id", item.id.to_string_lossy().into_owned()));
+
+    let width = fields.iter().map(|(name, _)| name.len()).max().unwrap_or(0) + 1;
+    let mut s = String::new();
+    for (name, value) in fields {
+        writeln!(s, "{:width$} {value}", format!("{name}:")).unwrap();
+    }
+    s
+}
+
+fn display_opt_path(path: Option<&Path>) -> String {
+    path.map(utils::path::display).unwrap_or_else(|| "-".to_string())
+}
+
+fn display_datetime(datetime: DateTime<Local>) -> String {
+    datetime.format("%d/%m/%Y %H:%M:%S").to_string()
+}
+
+/// Formats a unix mode like 'ls -l' does, for example `drwxr-xr-x`.
+#[cfg(unix)]
+fn display_mode(mode: u32) -> String {
+    let file_type = match mode & 0o170000 {
+        0o040000 => 'd',
+        0o120000 => 'l',
+        0o010000 => 'p',
+        0o020000 => 'c',
+        0o060000 => 'b',
+        0o140000 => 's',
+        _ => '-',
+    };
+    let mut s = String::from(file_type);
+    for shift in [6, 3, 0] {
+        let bits = (mode >> shift) & 0o7;
+        s.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        s.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        s.push(if bits & 0o1 != 0 { 'x' } else { '-' });
+    }
+    s
+}
@@ -0,0 +1,301 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, TimeZone, Utc};
+use clap::Parser;
+use serde::Serialize;
+use tabled::{builder::Builder, object::Segment, Alignment, Modify, Table};
+use trash::TrashItem;
+
+use crate::{app, trash_item, utils};
+
+use super::list;
+
+#[derive(Debug, Parser)]
+pub struct Args {
+    #[clap(flatten)]
+    query_args: list::QueryArgs,
+
+    /// Print the statistics as JSON
+    #[arg(long)]
+    json: bool,
+
+    /// How many original directories and extensions to show
+    #[arg(long, default_value_t = 10)]
+    top: usize,
+}
+
+impl Args {
+    pub fn run(&self, config_args: &app::ConfigArgs) -> Result<()> {
+        let items = self.query_args.list()?;
+        let stats = Stats::new(&items, self.top, self.query_args.filter_args.now()?);
+        let mut stdout = io::stdout().lock();
+        if self.json {
+            serde_json::to_writer_pretty(&mut stdout, &stats)?;
+            writeln!(stdout)?;
+        } else {
+            let use_table = config_args.table_status.merge(atty::is(atty::Stream::Stdout));
+            stats.write_tables(&mut stdout, use_table).context("Printing stats")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+struct Counts {
+    items: u64,
+    files: u64,
+    directories: u64,
+    bytes: u64,
+}
+
+impl Counts {
+    fn add(&mut self, other: &Counts) {
+        self.items += other.items;
+        self.files += other.files;
+        self.directories += other.directories;
+        self.bytes += other.bytes;
+    }
+
+    fn of_item(item: &TrashItem) -> Counts {
+        let mut counts = Counts { items: 1, ..Counts::default() };
+        if let Some(path) = trash_item::payload_path(item) {
+            counts.walk(&path);
+        }
+        counts
+    }
+
+    fn walk(&mut self, path: &Path) {
+        let meta = match fs::symlink_metadata(path) {
+            Ok(meta) => meta,
+            Err(_) => return,
+        };
+        if meta.is_dir() {
+            self.directories += 1;
+            for entry in fs::read_dir(path).into_iter().flatten().flatten() {
+                self.walk(&entry.path());
+            }
+        } else {
+            self.files += 1;
+            self.bytes += meta.len();
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct TrashDirStats {
+    path: String,
+    #[serde(flatten)]
+    counts: Counts,
+}
+
+#[derive(Debug, Serialize)]
+struct ItemSummary {
+    path: String,
+    deleted: String,
+}
+
+#[derive(Debug, Serialize)]
+struct AgeBucket {
+    age: &'static str,
+    items: u64,
+    bytes: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct Group<T> {
+    name: T,
+    items: u64,
+    bytes: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct Stats {
+    total: Counts,
+    trash_dirs: Vec<TrashDirStats>,
+    oldest: Option<ItemSummary>,
+    newest: Option<ItemSummary>,
+    ages: Vec<AgeBucket>,
+    top_directories: Vec<Group<String>>,
+    /// Items without an extension are grouped under `None`.
+    top_extensions: Vec<Group<Option<String>>>,
+}
+
+const AGE_BUCKETS: [(&str, i64); 6] = [
+    ("< 1 hour", 60 * 60),
+    ("< 1 day", 24 * 60 * 60),
+    ("< 1 week", 7 * 24 * 60 * 60),
+    ("< 1 month", 30 * 24 * 60 * 60),
+    ("< 1 year", 365 * 24 * 60 * 60),
+    (">= 1 year", i64::MAX),
+];
+
+impl Stats {
+    /// The statistics of `items`, where their ages are relative to `now`.
+    fn new(items: &[TrashItem], top: usize, now: DateTime<Utc>) -> Stats {
+        let now = now.timestamp();
+        let mut total = Counts::default();
+        let mut trash_dirs = HashMap::<String, Counts>::new();
+        let mut ages: Vec<_> =
+            AGE_BUCKETS.iter().map(|(age, _)| AgeBucket { age, items: 0, bytes: 0 }).collect();
+        let mut directories = HashMap::<String, (u64, u64)>::new();
+        let mut extensions = HashMap::<Option<String>, (u64, u64)>::new();
+        for item in items {
+            let counts = Counts::of_item(item);
+            total.add(&counts);
+            let trash_dir =
+                trash_item::trash_dir(item).map(|p| utils::path::display(&p)).unwrap_or_default();
+            trash_dirs.entry(trash_dir).or_default().add(&counts);
+
+            let age = now - item.time_deleted;
+            let bucket = AGE_BUCKETS.iter().position(|(_, limit)| age < *limit).unwrap();
+            ages[bucket].items += 1;
+            ages[bucket].bytes += counts.bytes;
+
+            let directory = directories.entry(utils::path::display(&item.original_parent));
+            let directory = directory.or_default();
+            directory.0 += 1;
+            directory.1 += counts.bytes;
+
+            let extension = Path::new(&item.name)
+                .extension()
+                .map(|extension| extension.to_string_lossy().into_owned());
+            let extension = extensions.entry(extension).or_default();
+            extension.0 += 1;
+            extension.1 += counts.bytes;
+        }
+
+        let mut trash_dirs: Vec<_> =
+            trash_dirs.into_iter().map(|(path, counts)| TrashDirStats { path, counts }).collect();
+        trash_dirs.sort_by(|a, b| a.path.cmp(&b.path));
+        let summary = |item: &TrashItem| ItemSummary {
+            path: utils::path::display(&item.original_path()),
+            deleted: Local.timestamp_opt(item.time_deleted, 0).unwrap().to_rfc3339(),
+        };
+        Stats {
+            total,
+            trash_dirs,
+            oldest: items.iter().min_by_key(|item| item.time_deleted).map(summary),
+            newest: items.iter().max_by_key(|item| item.time_deleted).map(summary),
+            ages,
+            top_directories: top_groups(directories, top),
+            top_extensions: top_groups(extensions, top),
+        }
+    }
+
+    fn write_tables(&self, out: &mut impl Write, use_table: bool) -> io::Result<()> {
+        let size = utils::display_size;
+        let mut tables = Vec::new();
+        let mut builder = Builder::default();
+        builder.set_columns(["Trash", "Items", "Files", "Directories", "Size"]);
+        for TrashDirStats { path, counts } in self.trash_dirs.iter() {
+            builder.add_record(counts_row(path.clone(), counts));
+        }
+        builder.add_record(counts_row("Total".to_string(), &self.total));
+        tables.push(style_table(builder.build(), use_table));
+
+        let mut builder = Builder::default();
+        builder.set_columns(["", "Deleted", "Path"]);
+        for (name, summary) in [("Oldest", &self.oldest), ("Newest", &self.newest)] {
+            if let Some(summary) = summary {
+                builder.add_record([
+                    name.to_string(),
+                    summary.deleted.clone(),
+                    summary.path.clone(),
+                ]);
+            }
+        }
+        tables.push(style_table(builder.build(), use_table));
+
+        let mut builder = Builder::default();
+        builder.set_columns(["Age", "Items", "Size"]);
+        for bucket in &self.ages {
+            builder.add_record([
+                bucket.age.to_string(),
+                bucket.items.to_string(),
+                size(bucket.bytes),
+            ]);
+        }
+        tables.push(style_table(builder.build(), use_table));
+
+        let directories = self
+            .top_directories
+            .iter()
+            .map(|group| (group.name.as_str(), group.items, group.bytes));
+        let extensions = self
+            .top_extensions
+            .iter()
+            .map(|group| (group.name.as_deref().unwrap_or("(none)"), group.items, group.bytes));
+        for (header, groups) in
+            [("Directory", directories.collect::<Vec<_>>()), ("Extension", extensions.collect())]
+        {
+            let mut builder = Builder::default();
+            builder.set_columns([header, "Items", "Size"]);
+            for (name, items, bytes) in groups {
+                builder.add_record([name.to_string(), items.to_string(), size(bytes)]);
+            }
+            tables.push(style_table(builder.build(), use_table));
+        }
+        for (i, table) in tables.iter().enumerate() {
+            if i > 0 {
+                writeln!(out)?;
+            }
+            writeln!(out, "{table}")?;
+        }
+        Ok(())
+    }
+}
+
+fn counts_row(name: String, counts: &Counts) -> [String; 5] {
+    [
+        name,
+        counts.items.to_string(),
+        counts.files.to_string(),
+        counts.directories.to_string(),
+        utils::display_size(counts.bytes),
+    ]
+}
+
+fn style_table(mut table: Table, use_table: bool) -> Table {
+    table.with(Modify::new(Segment::all()).with(Alignment::left()));
+    if use_table {
+        table.with(tabled::Style::rounded());
+    } else {
+        table.with(tabled::Style::empty());
+    }
+    table
+}
+
+/// The `top` groups with the most bytes, then the most items, then by name.
+fn top_groups<T: Ord>(groups: HashMap<T, (u64, u64)>, top: usize) -> Vec<Group<T>> {
+    let mut groups: Vec<_> =
+        groups.into_iter().map(|(name, (items, bytes))| Group { name, items, bytes }).collect();
+    groups.sort_by(|a, b| {
+        b.bytes.cmp(&a.bytes).then(b.items.cmp(&a.items)).then_with(|| a.name.cmp(&b.name))
+    });
+    groups.truncate(top);
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn top_groups_order() {
+        let groups: HashMap<_, _> = [
+            (Some("b"), (1, 10)),
+            (None, (1, 10)),
+            (Some("a"), (1, 10)),
+            (Some("c"), (2, 10)),
+            (Some("d"), (1, 20)),
+        ]
+        .into_iter()
+        .collect();
+        let names: Vec<_> = top_groups(groups, 4).into_iter().map(|group| group.name).collect();
+        assert_eq!(names, [Some("d"), Some("c"), None, Some("a")]);
+    }
+}
@@ -0,0 +1,615 @@
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::{cmp, env, fs, iter};
+
+use anyhow::{bail, Context, Result};
+use clap::{Parser, ValueEnum};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use ratatui::backend::{Backend, CrosstermBackend};
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Span, Spans};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{Frame, Terminal};
+use trash::TrashItem;
+
+use crate::app::{self, TimeDisplayMode};
+use crate::exitcode::PromptError;
+use crate::filter::{Case, Filter, Match, MatchOn, PatternSet};
+use crate::{trash_item, utils};
+
+use super::utils::is_typed_confirmation;
+use super::{list, show};
+
+/// How many bytes of a file are shown in the preview.
+const PREVIEW_BYTES: u64 = 64 * 1024;
+
+#[derive(Debug, Parser)]
+pub struct Args {
+    #[clap(flatten)]
+    query_args: list::QueryArgs,
+}
+
+impl Args {
+    pub fn run(&self, config_args: &app::ConfigArgs) -> Result<()> {
+        if !atty::is(atty::Stream::Stdin) || !atty::is(atty::Stream::Stdout) {
+            return Err(PromptError::NotInteractive(
+                "`trashy tui` needs an interactive terminal".to_string(),
+            )
+            .into());
+        }
+        let mut app = App::new(&self.query_args, config_args)?;
+        let mut terminal = TerminalGuard::new()?;
+        app.run(&mut terminal.0)
+    }
+}
+
+/// Restores the terminal when dropped, even if the app returns an error.
+struct TerminalGuard(Terminal<CrosstermBackend<io::Stdout>>);
+
+impl TerminalGuard {
+    fn new() -> Result<TerminalGuard> {
+        let terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+        enable_raw_mode()?;
+        let mut guard = TerminalGuard(terminal);
+        execute!(guard.0.backend_mut(), EnterAlternateScreen)?;
+        Ok(guard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = disable_raw_mode();
+        let _ = execute!(self.0.backend_mut(), LeaveAlternateScreen);
+        let _ = self.0.show_cursor();
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Action {
+    Restore,
+    Purge,
+}
+
+enum Mode {
+    Normal,
+    /// Typing the live filter pattern.
+    Filter,
+    /// Asking whether to restore, answered with a single key.
+    ConfirmRestore,
+    /// Typing the number of items or "yes" to confirm removing them permanently.
+    ConfirmPurge(String),
+    /// Typing the directory to restore to.
+    RestoreTo(String),
+}
+
+struct App<'a> {
+    query_args: &'a list::QueryArgs,
+    use_color: bool,
+    time_display_mode: TimeDisplayMode,
+    items: Vec<TrashItem>,
+    /// Whether each item in `items` is selected.
+    selected: Vec<bool>,
+    /// Indices into `items` of the items that match the live filter.
+    visible: Vec<usize>,
+    state: ListState,
+    pattern: String,
+    r#match: Match,
+    mode: Mode,
+    /// The message shown in the status line and whether it is an error.
+    message: Option<(String, bool)>,
+    /// The preview of the item at an index into `items`.
+    preview: Option<(usize, Vec<String>)>,
+    quit: bool,
+}
+
+impl<'a> App<'a> {
+    fn new(query_args: &'a list::QueryArgs, config_args: &app::ConfigArgs) -> Result<App<'a>> {
+        let mut app = App {
+            query_args,
+            use_color: config_args.color_status.merge(true),
+            time_display_mode: config_args.time_display_mode,
+            items: Vec::new(),
+            selected: Vec::new(),
+            visible: Vec::new(),
+            state: ListState::default(),
+            pattern: String::new(),
+            r#match: query_args.filter_args.options.r#match,
+            mode: Mode::Normal,
+            message: None,
+            preview: None,
+            quit: false,
+        };
+        app.reload()?;
+        Ok(app)
+    }
+
+    fn run<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<()> {
+        while !self.quit {
+            terminal.draw(|f| self.draw(f))?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    self.handle_key(key)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Lists the trash again, for example after items were restored.
+    fn reload(&mut self) -> Result<()> {
+        self.items = self.query_args.list()?;
+        self.selected = vec![false; self.items.len()];
+        self.preview = None;
+        self.refresh_visible();
+        Ok(())
+    }
+
+    fn refresh_visible(&mut self) {
+        if self.pattern.is_empty() {
+            self.visible = (0..self.items.len()).collect();
+        } else {
+            match PatternSet::new(self.r#match, iter::once(&self.pattern), Case::Smart, 0) {
+                Ok(patterns) => {
+                    let filter = Filter::PatternSet(patterns, MatchOn::Path);
+                    self.visible = (0..self.items.len())
+                        .filter(|&i| filter.is_match(&self.items[i]))
+                        .collect();
+                    if let Match::Fuzzy = self.r#match {
+                        self.visible
+                            .sort_by_cached_key(|&i| cmp::Reverse(filter.score(&self.items[i])));
+                    }
+                    self.message = None;
+                }
+                // keep showing the previous matches while the pattern is incomplete
+                Err(e) => self.message = Some((format!("{e:#}"), true)),
+            }
+        }
+        let cursor = self.state.selected().unwrap_or(0);
+        self.state.select(match self.visible.len() {
+            0 => None,
+            len => Some(cmp::min(cursor, len - 1)),
+        });
+    }
+
+    /// The index into `items` of the item under the cursor.
+    fn current(&self) -> Option<usize> {
+        self.state.selected().map(|cursor| self.visible[cursor])
+    }
+
+    /// The items that actions apply to: the selected items, or else the item under the cursor.
+    fn targets(&self) -> Vec<usize> {
+        let selected: Vec<_> = (0..self.items.len()).filter(|&i| self.selected[i]).collect();
+        if selected.is_empty() {
+            self.current().into_iter().collect()
+        } else {
+            selected
+        }
+    }
+
+    fn move_cursor(&mut self, delta: isize) {
+        if let Some(cursor) = self.state.selected() {
+            let last = self.visible.len() as isize - 1;
+            self.state.select(Some((cursor as isize + delta).clamp(0, last) as usize));
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Result<()> {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.quit = true;
+            return Ok(());
+        }
+        match &mut self.mode {
+            Mode::Normal => self.handle_normal_key(key),
+            Mode::Filter => {
+                match key.code {
+                    KeyCode::Enter | KeyCode::Esc => self.mode = Mode::Normal,
+                    KeyCode::Tab => self.cycle_match(),
+                    KeyCode::Backspace => {
+                        self.pattern.pop();
+                        self.refresh_visible();
+                    }
+                    KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        self.pattern.clear();
+                        self.refresh_visible();
+                    }
+                    KeyCode::Char(c) => {
+                        self.pattern.push(c);
+                        self.refresh_visible();
+                    }
+                    _ => (),
+                }
+                Ok(())
+            }
+            Mode::ConfirmRestore => {
+                self.mode = Mode::Normal;
+                match key.code {
+                    KeyCode::Char('y') => self.perform(Action::Restore),
+                    _ => Ok(()),
+                }
+            }
+            Mode::ConfirmPurge(input) => {
+                match key.code {
+                    KeyCode::Esc => self.mode = Mode::Normal,
+                    KeyCode::Enter => {
+                        let answer = std::mem::take(input);
+                        self.mode = Mode::Normal;
+                        if is_typed_confirmation(&answer, self.targets().len()) {
+                            return self.perform(Action::Purge);
+                        }
+                        self.message = Some(("Nothing was removed".to_string(), false));
+                    }
+                    KeyCode::Backspace => {
+                        input.pop();
+                    }
+                    KeyCode::Char(c) => input.push(c),
+                    _ => (),
+                }
+                Ok(())
+            }
+            Mode::RestoreTo(input) => {
+                match key.code {
+                    KeyCode::Esc => self.mode = Mode::Normal,
+                    KeyCode::Enter => {
+                        let dir = expand_tilde(input);
+                        self.mode = Mode::Normal;
+                        return self.restore_to(&dir);
+                    }
+                    KeyCode::Backspace => {
+                        input.pop();
+                    }
+                    KeyCode::Char(c) => input.push(c),
+                    _ => (),
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn handle_normal_key(&mut self, key: KeyEvent) -> Result<()> {
+        self.message = None;
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Char('j') | KeyCode::Down => self.move_cursor(1),
+            KeyCode::Char('k') | KeyCode::Up => self.move_cursor(-1),
+            KeyCode::PageDown => self.move_cursor(10),
+            KeyCode::PageUp => self.move_cursor(-10),
+            KeyCode::Char('g') | KeyCode::Home => self.move_cursor(isize::MIN / 2),
+            KeyCode::Char('G') | KeyCode::End => self.move_cursor(isize::MAX / 2),
+            KeyCode::Char(' ') => {
+                if let Some(i) = self.current() {
+                    self.selected[i] = !self.selected[i];
+                    self.move_cursor(1);
+                }
+            }
+            KeyCode::Char('a') => {
+                let select = !self.visible.iter().all(|&i| self.selected[i]);
+                for &i in &self.visible {
+                    self.selected[i] = select;
+                }
+            }
+            KeyCode::Char('/') => self.mode = Mode::Filter,
+            KeyCode::Tab => self.cycle_match(),
+            KeyCode::Char('r') if !self.targets().is_empty() => self.mode = Mode::ConfirmRestore,
+            KeyCode::Char('d') | KeyCode::Delete if !self.targets().is_empty() => {
+                self.mode = Mode::ConfirmPurge(String::new())
+            }
+            KeyCode::Char('t') if !self.targets().is_empty() => {
+                self.mode = Mode::RestoreTo(String::new())
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    fn cycle_match(&mut self) {
+        let variants = Match::value_variants();
+        let i =
+            variants.iter().position(|m| m.to_possible_value() == self.r#match.to_possible_value());
+        self.r#match = variants[(i.unwrap_or(0) + 1) % variants.len()];
+        self.refresh_visible();
+    }
+
+    fn target_items(&self) -> Vec<TrashItem> {
+        self.targets().into_iter().map(|i| utils::clone_trash_item(&self.items[i])).collect()
+    }
+
+    fn perform(&mut self, action: Action) -> Result<()> {
+        let items = self.target_items();
+        let len = items.len();
+        let result = match action {
+            Action::Restore => trash::os_limited::restore_all(items),
+            Action::Purge => trash::os_limited::purge_all(items),
+        };
+        let message = match (result, action) {
+            (Ok(()), Action::Restore) => (format!("Restored {}", plural_items(len)), false),
+            (Ok(()), Action::Purge) => {
+                (format!("Permanently removed {}", plural_items(len)), false)
+            }
+            (Err(e), _) => (format!("{e}"), true),
+        };
+        // reloading clears the message when a filter is active
+        self.reload()?;
+        self.message = Some(message);
+        Ok(())
+    }
+
+    fn restore_to(&mut self, dir: &Path) -> Result<()> {
+        let items = self.target_items();
+        let len = items.len();
+        let mut restored = 0;
+        let result = items.iter().try_for_each(|item| {
+            restore_to(item, dir)?;
+            restored += 1;
+            Ok::<_, anyhow::Error>(())
+        });
+        let message = match result {
+            Ok(()) => (format!("Restored {} to `{}`", plural_items(len), dir.display()), false),
+            Err(e) if restored == 0 => (format!("{e:#}"), true),
+            Err(e) => (format!("Restored only {restored} of {}: {e:#}", plural_items(len)), true),
+        };
+        self.reload()?;
+        self.message = Some(message);
+        Ok(())
+    }
+
+    fn preview(&mut self) -> &[String] {
+        let current = self.current();
+        match (&self.preview, current) {
+            (Some((i, _)), Some(current)) if *i == current => (),
+            (_, Some(current)) => {
+                let item = &self.items[current];
+                let mut lines: Vec<_> =
+                    show::display_details(current as u32, item).lines().map(String::from).collect();
+                lines.push(String::new());
+                lines.extend(preview_contents(item));
+                self.preview = Some((current, lines));
+            }
+            (_, None) => self.preview = None,
+        }
+        self.preview.as_ref().map_or(&[], |(_, lines)| lines)
+    }
+
+    fn draw<B: Backend>(&mut self, f: &mut Frame<B>) {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(1), Constraint::Length(1)])
+            .split(f.size());
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+            .split(rows[0]);
+
+        let list_items: Vec<_> = self
+            .visible
+            .iter()
+            .map(|&i| {
+                let item = &self.items[i];
+                ListItem::new(Spans::from(vec![
+                    Span::styled(
+                        if self.selected[i] { "* " } else { "  " },
+                        Style::default().fg(Color::Yellow),
+                    ),
+                    Span::raw(format!("{i:>4}  ")),
+                    Span::styled(
+                        format!("{:<16} ", list::display_item_date(item, self.time_display_mode)),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                    Span::styled(
+                        utils::path::display(&item.original_path()),
+                        self.path_style(item),
+                    ),
+                ]))
+            })
+            .collect();
+        let title = format!(" Trash ({}/{}) ", self.visible.len(), self.items.len());
+        let list = List::new(list_items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        f.render_stateful_widget(list, columns[0], &mut self.state);
+
+        let preview: Vec<_> =
+            self.preview().iter().map(|line| Spans::from(line.as_str())).collect();
+        let preview = Paragraph::new(preview)
+            .block(Block::default().borders(Borders::ALL).title(" Preview "))
+            .wrap(Wrap { trim: false });
+        f.render_widget(preview, columns[1]);
+
+        let selected = self.selected.iter().filter(|&&selected| selected).count();
+        let status = match &self.message {
+            Some((message, true)) => Span::styled(message.clone(), Style::default().fg(Color::Red)),
+            Some((message, false)) => Span::raw(message.clone()),
+            None => Span::styled(
+                format!(
+                    "{selected} selected | space: select  a: all  /: filter  tab: match mode  \
+                     r: restore  t: restore to  d: delete  q: quit"
+                ),
+                Style::default().fg(Color::DarkGray),
+            ),
+        };
+        f.render_widget(Paragraph::new(status), rows[1]);
+
+        let targets = plural_items(self.targets().len());
+        let match_name = self.r#match.to_possible_value().unwrap();
+        let (prompt, input) = match &self.mode {
+            Mode::Normal | Mode::Filter => {
+                (format!("{}> ", match_name.get_name()), self.pattern.as_str())
+            }
+            Mode::ConfirmRestore => (format!("Restore {targets}? [y/n] "), ""),
+            Mode::ConfirmPurge(input) => (
+                format!(
+                    "PERMANENTLY delete {targets}? Type {} or 'yes' to confirm: ",
+                    self.targets().len()
+                ),
+                input.as_str(),
+            ),
+            Mode::RestoreTo(input) => (format!("Restore {targets} to: "), input.as_str()),
+        };
+        let prompt_style = match self.mode {
+            Mode::ConfirmRestore | Mode::ConfirmPurge(_) => {
+                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+            }
+            _ => Style::default().fg(Color::Cyan),
+        };
+        let line = Spans::from(vec![Span::styled(prompt.clone(), prompt_style), Span::raw(input)]);
+        f.render_widget(Paragraph::new(line), rows[2]);
+        if let Mode::Filter | Mode::ConfirmPurge(_) | Mode::RestoreTo(_) = self.mode {
+            let x = rows[2].x + (prompt.chars().count() + input.chars().count()) as u16;
+            f.set_cursor(cmp::min(x, rows[2].right().saturating_sub(1)), rows[2].y);
+        }
+    }
+
+    fn path_style(&self, item: &TrashItem) -> Style {
+        if !self.use_color {
+            return Style::default();
+        }
+        match list::item_lscolors(item) {
+            Ok(Some(style)) => lscolors_style(&style),
+            _ => Style::default(),
+        }
+    }
+}
+
+fn plural_items(len: usize) -> String {
+    format!("{len} item{}", if len == 1 { "" } else { "s" })
+}
+
+fn expand_tilde(input: &str) -> PathBuf {
+    match env::var_os("HOME") {
+        Some(home) if input == "~" => PathBuf::from(home),
+        Some(home) if input.starts_with("~/") => Path::new(&home).join(&input[2..]),
+        _ => PathBuf::from(input),
+    }
+}
+
+/// Moves the trashed file into `dir` and removes it from the trash.
+fn restore_to(item: &TrashItem, dir: &Path) -> Result<()> {
+    let (payload, info) = match (trash_item::payload_path(item), trash_item::info_path(item)) {
+        (Some(payload), Some(info)) => (payload, info),
+        _ => bail!("Restoring to another directory is not supported on this platform"),
+    };
+    if !dir.is_dir() {
+        bail!("`{}` is not a directory", dir.display());
+    }
+    let destination = dir.join(&item.name);
+    if fs::symlink_metadata(&destination).is_ok() {
+        bail!("`{}` already exists", destination.display());
+    }
+    move_path(&payload, &destination).with_context(|| {
+        format!("Failed to move `{}` to `{}`", payload.display(), destination.display())
+    })?;
+    fs::remove_file(&info).with_context(|| format!("Failed to remove `{}`", info.display()))?;
+    Ok(())
+}
+
+/// Renames `from` to `to`, or copies and then removes it if they are on different file systems.
+fn move_path(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to) {
+        #[cfg(unix)]
+        Err(e) if e.raw_os_error() == Some(libc::EXDEV) => {
+            if let Err(e) = copy_recursively(from, to) {
+                // don't leave a partial copy behind, the original is still in the trash
+                let _ = remove_recursively(to);
+                return Err(e);
+            }
+            remove_recursively(from)
+        }
+        result => result,
+    }
+}
+
+#[cfg(unix)]
+fn copy_recursively(from: &Path, to: &Path) -> io::Result<()> {
+    let meta = fs::symlink_metadata(from)?;
+    if meta.file_type().is_symlink() {
+        std::os::unix::fs::symlink(fs::read_link(from)?, to)
+    } else if meta.is_dir() {
+        fs::create_dir(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursively(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        fs::set_permissions(to, meta.permissions())
+    } else {
+        fs::copy(from, to).map(|_| ())
+    }
+}
+
+#[cfg(unix)]
+fn remove_recursively(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// The start of a file or the entries of a directory.
+fn preview_contents(item: &TrashItem) -> Vec<String> {
+    let path = match trash_item::payload_path(item) {
+        Some(path) => path,
+        None => return Vec::new(),
+    };
+    let meta = match fs::symlink_metadata(&path) {
+        Ok(meta) => meta,
+        Err(_) => return Vec::new(),
+    };
+    if meta.is_dir() {
+        match trash_item::dir_entries(&path) {
+            Ok(entries) if entries.is_empty() => vec!["(empty directory)".to_string()],
+            Ok(entries) => entries,
+            Err(e) => vec![format!("Failed to read directory: {e}")],
+        }
+    } else if meta.is_file() {
+        let mut buf = Vec::new();
+        match fs::File::open(&path).and_then(|file| file.take(PREVIEW_BYTES).read_to_end(&mut buf))
+        {
+            Ok(_) if buf.contains(&0) => vec!["(binary file)".to_string()],
+            Ok(_) => {
+                String::from_utf8_lossy(&buf).lines().map(|l| l.replace('\t', "    ")).collect()
+            }
+            Err(e) => vec![format!("Failed to read file: {e}")],
+        }
+    } else {
+        Vec::new()
+    }
+}
+
+fn lscolors_style(style: &lscolors::Style) -> Style {
+    let mut tui_style = Style::default();
+    if let Some(color) = &style.foreground {
+        tui_style = tui_style.fg(lscolors_color(color));
+    }
+    if style.font_style.bold {
+        tui_style = tui_style.add_modifier(Modifier::BOLD);
+    }
+    tui_style
+}
+
+fn lscolors_color(color: &lscolors::Color) -> Color {
+    use lscolors::Color as C;
+    match *color {
+        C::Black => Color::Black,
+        C::Red => Color::Red,
+        C::Green => Color::Green,
+        C::Yellow => Color::Yellow,
+        C::Blue => Color::Blue,
+        C::Magenta => Color::Magenta,
+        C::Cyan => Color::Cyan,
+        C::White => Color::Gray,
+        C::BrightBlack => Color::DarkGray,
+        C::BrightRed => Color::LightRed,
+        C::BrightGreen => Color::LightGreen,
+        C::BrightYellow => Color::LightYellow,
+        C::BrightBlue => Color::LightBlue,
+        C::BrightMagenta => Color::LightMagenta,
+        C::BrightCyan => Color::LightCyan,
+        C::BrightWhite => Color::White,
+        C::Fixed(n) => Color::Indexed(n),
+        C::RGB(r, g, b) => Color::Rgb(r, g, b),
+    }
+}
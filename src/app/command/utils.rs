@@ -1,7 +1,18 @@
-use super::list;
+use std::iter;
+use std::path::Path;
+
+use either::Either::Right;
+
+use super::list::{self, SortBy};
+use super::show;
+use crate::exitcode::PromptError;
+use crate::filter::{Case, Filters, Match, PatternSet};
+use crate::utils::swap;
 use crate::{app, range_set::RangeSet, range_syntax, trash_item::MaybeIndexedTrashItems};
-use anyhow::Result;
+use anyhow::{bail, Result};
 use clap::{ArgAction, Parser};
+use serde::{Deserialize, Serialize};
+use trash::TrashItem;
 
 pub fn on_items_with_prompt(
     items: MaybeIndexedTrashItems,
@@ -11,25 +22,256 @@ pub fn on_items_with_prompt(
 ) -> Result<()> {
     use dialoguer::Confirm;
 
+    ensure_interactive("ask for confirmation", "use '--yes' to skip it")?;
+    display_pending_items(&items, config_args, action_name)?;
+    if !interacted(Confirm::new().with_prompt("Are you sure?").interact())? {
+        return Err(PromptError::Declined(format!("Nothing was {action_name}")).into());
+    }
+    f(items)
+}
+
+/// Like `on_items_with_prompt`, but the number of items or "yes" has to be typed to confirm.
+/// This is used for actions that can't be undone.
+pub fn on_items_with_typed_prompt(
+    items: MaybeIndexedTrashItems,
+    config_args: &app::ConfigArgs,
+    action_name: &str,
+    f: impl FnOnce(MaybeIndexedTrashItems) -> Result<()>,
+) -> Result<()> {
+    use dialoguer::Input;
+
+    ensure_interactive("ask for confirmation", "use '--yes' to skip it")?;
+    display_pending_items(&items, config_args, action_name)?;
+    let len = items.len();
+    let answer = interacted(
+        Input::<String>::new()
+            .with_prompt(format!("Type {len} or 'yes' to confirm"))
+            .allow_empty(true)
+            .interact_text(),
+    )?;
+    if !is_typed_confirmation(&answer, len) {
+        return Err(PromptError::Declined(format!("Nothing was {action_name}")).into());
+    }
+    f(items)
+}
+
+/// Whether `answer` to a typed prompt confirms acting on `len` items.
+pub fn is_typed_confirmation(answer: &str, len: usize) -> bool {
+    let answer = answer.trim();
+    answer == len.to_string() || answer.eq_ignore_ascii_case("yes")
+}
+
+/// Asks for each item whether to act on it, then acts on the accepted items.
+pub fn on_each_item_with_prompt(
+    items: MaybeIndexedTrashItems,
+    config_args: &app::ConfigArgs,
+    action_name: &str,
+    f: impl FnOnce(MaybeIndexedTrashItems) -> Result<()>,
+) -> Result<()> {
+    use dialoguer::Select;
+
+    ensure_interactive("ask for confirmation", "use '--yes' to skip it")?;
+    let mut accepted = Vec::new();
+    let mut skipped = 0;
+    let mut items = items.into_indexed_items().into_iter();
+    while let Some((i, item)) = items.next() {
+        print!("{}", show::display_details(i, &item));
+        let answer = interacted(
+            Select::new()
+                .with_prompt(format!("Should this item be {action_name}?"))
+                .items(&["yes", "no", "all remaining items", "quit"])
+                .default(0)
+                .interact(),
+        )?;
+        println!();
+        match answer {
+            0 => accepted.push((i, item)),
+            1 => skipped += 1,
+            2 => {
+                accepted.push((i, item));
+                accepted.extend(items.by_ref());
+                break;
+            }
+            _ => {
+                skipped += 1 + items.len();
+                break;
+            }
+        }
+    }
+    let len = accepted.len();
+    let plural = if len == 1 { "" } else { "s" };
+    println!("{len} item{plural} will be {action_name}, {skipped} skipped");
+    if accepted.is_empty() {
+        return Err(PromptError::Declined(format!("Nothing was {action_name}")).into());
+    }
+    let accepted = MaybeIndexedTrashItems(Right(accepted));
+    list::display_indexed_items(accepted.indexed_items(), config_args)?;
+    f(accepted)
+}
+
+fn display_pending_items(
+    items: &MaybeIndexedTrashItems,
+    config_args: &app::ConfigArgs,
+    action_name: &str,
+) -> Result<()> {
     let len = items.len();
     let plural = if len == 1 { "" } else { "s" };
     println!("{len} item{plural} will be {action_name}");
-    list::display_indexed_items(items.indexed_items(), config_args)?;
-    if Confirm::new().with_prompt("Are you sure?").interact()? {
-        f(items)?;
+    list::display_indexed_items(items.indexed_items(), config_args)
+}
+
+/// Fails with [`PromptError::NotInteractive`] if there is no terminal to `what` in.
+/// `hint` tells how to get by without prompting.
+pub fn ensure_interactive(what: &str, hint: &str) -> Result<()> {
+    if !atty::is(atty::Stream::Stdin) || !atty::is(atty::Stream::Stderr) {
+        return Err(PromptError::NotInteractive(format!(
+            "Can't {what} without an interactive terminal, {hint}"
+        ))
+        .into());
     }
     Ok(())
 }
 
+/// Turns an interrupted prompt into [`PromptError::Interrupted`].
+fn interacted<T>(result: std::io::Result<T>) -> Result<T> {
+    match result {
+        Ok(value) => Ok(value),
+        Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {
+            // dialoguer hides the cursor while prompting and doesn't get to show it again
+            let _ = dialoguer::console::Term::stderr().show_cursor();
+            Err(PromptError::Interrupted.into())
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Shows `items` and asks which of them to act on, in the syntax of '--ranges'.
+pub fn prompt_ranges(
+    items: &[TrashItem],
+    config_args: &app::ConfigArgs,
+    action: &str,
+) -> Result<Vec<(u32, TrashItem)>> {
+    if items.is_empty() {
+        bail!("No trash items to choose from");
+    }
+    ensure_interactive(
+        &format!("ask which items to {action}"),
+        "use filters or '--ranges' to choose them",
+    )?;
+    list::display_items(items, config_args)?;
+    let ranges = interacted(
+        dialoguer::Input::<String>::new().with_prompt(format!("{action} ranges")).interact_text(),
+    )?;
+    list::filter_by_ranges(items, range_syntax::parse_range_set(&ranges, items.len() as u32)?)
+}
+
 #[derive(Debug, Parser)]
 pub struct Force {
     /// Skip confirmation
     ///
     /// By default, 'trashy' will ask for confirmation before restoring or permanently removing files.
-    /// You can opt out of this by adding '--force'.
-    /// This can be useful in scripts.
-    #[arg(short, long)]
+    /// You can opt out of this by adding '--force' or its alias '--yes'.
+    /// This is needed in scripts, where there is no terminal to ask in.
+    #[arg(short, long, visible_alias = "yes", short_alias = 'y')]
     pub force: bool,
+
+    /// How to ask for confirmation
+    ///
+    /// When asking for each item, the answer can also be to accept all remaining items or to quit.
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Interactive::Once,
+        conflicts_with = "force",
+        verbatim_doc_comment
+    )]
+    pub interactive: Interactive,
+}
+
+#[derive(clap::ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Interactive {
+    /// Ask once for all items.
+    Once,
+    /// Ask for each item, showing its details.
+    Each,
+}
+
+#[derive(Debug, Parser)]
+pub struct Pick {
+    /// Pick the items from a list
+    ///
+    /// Shows the trash items matching the other options in a list,
+    /// where items are selected with space and confirmed with enter.
+    /// Before that, the list can be narrowed down by typing a fuzzy pattern for the original paths.
+    /// Unlike piping 'trashy list' into other programs, this works with any file name.
+    #[arg(long, conflicts_with = "ranges", verbatim_doc_comment)]
+    pub pick: bool,
+}
+
+impl Pick {
+    /// Lets the user pick some of `items`, in the same order as 'trashy list' shows them.
+    ///
+    /// The list can first be narrowed down by typing a fuzzy pattern for the original paths.
+    pub fn pick(
+        items: Vec<TrashItem>,
+        config_args: &app::ConfigArgs,
+    ) -> Result<Vec<(u32, TrashItem)>> {
+        if items.is_empty() {
+            bail!("No trash items to pick from");
+        }
+        ensure_interactive("pick items", "use filters or '--ranges' to choose them")?;
+        let shown = loop {
+            let pattern = interacted(
+                dialoguer::Input::<String>::new()
+                    .with_prompt("Type to narrow down the items (fuzzy), or enter to show all")
+                    .allow_empty(true)
+                    .interact_text(),
+            )?;
+            let patterns = PatternSet::new(Match::Fuzzy, iter::once(&pattern), Case::Smart, 0)?;
+            let shown: Vec<_> = (0..items.len())
+                .rev()
+                .filter(|&i| {
+                    pattern.is_empty()
+                        || patterns.is_match(&items[i].original_path().to_string_lossy())
+                })
+                .collect();
+            if !shown.is_empty() {
+                break shown;
+            }
+            eprintln!("No trash item matches `{pattern}`");
+        };
+        let use_color = config_args.color_status.merge(atty::is(atty::Stream::Stderr));
+        let labels = shown
+            .iter()
+            .map(|&i| {
+                let (time, path) = list::display_item(
+                    &items[i],
+                    use_color,
+                    config_args.time_display_mode,
+                    Path::new(""),
+                )?;
+                Ok(format!("{i:>4}  {time:<16} {path}"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let picked = interacted(
+            dialoguer::MultiSelect::new()
+                .with_prompt("Pick items with space, confirm with enter")
+                .items(&labels)
+                .report(false)
+                .interact_opt(),
+        )?
+        .unwrap_or_default();
+        if picked.is_empty() {
+            return Err(PromptError::Declined("Nothing was picked".to_string()).into());
+        }
+        // the labels are the shown items in reverse, so turn their positions back into indices
+        let mut is_picked = vec![false; items.len()];
+        for j in picked {
+            is_picked[shown[j]] = true;
+        }
+        Ok(items.into_iter().zip(0..).filter(|(_, i)| is_picked[*i as usize]).map(swap).collect())
+    }
 }
 
 #[derive(Debug, Parser)]
@@ -40,13 +282,22 @@ pub struct Ranges {
     /// To find the ranges, look at the 'i' column in the table shown by 'trashy list'.
     /// The option is called '--ranges' but you can also use individual indices by just typing out the number.
     /// Ranges are zero based, inclusive at the start, and exclusive at the end, just like rust ranges.
+    /// Use 'A..=B' to include the end, and leave out the start or end to go from the first or to the last item.
+    /// Negative indices count from the end, so '-1' is the item with the highest index.
+    /// Ranges can be separated by spaces or commas, and ranges starting with '^' are excluded.
+    /// When used together with filters, sorting or '--max', the indices refer to the items shown by
+    /// 'trashy list' with the same options.
     /// Examples:
     ///     --ranges='1 5..9 10..12'
-    ///     --ranges='1 4 5 6'
+    ///     --glob '**/*.rs' --ranges='2..5'
+    ///     --ranges='1,4,5,6'
+    ///     --ranges='..=5 ^3'
+    ///     --ranges='-3..'
+    ///     --ranges='^0'
     #[arg(
        short,
        long,
-       conflicts_with_all = list::QueryArgs::CONFLICTS,
+       allow_hyphen_values = true,
        action = ArgAction::Append,
        verbatim_doc_comment
     )]
@@ -54,7 +305,73 @@ pub struct Ranges {
 }
 
 impl Ranges {
-    pub fn parse(&self) -> Result<RangeSet> {
-        self.ranges.iter().flat_map(|s| range_syntax::parse_ranges(s)).collect()
+    /// Parses the ranges, where `len` is the number of items the indices refer to.
+    pub fn parse(&self, len: usize) -> Result<RangeSet> {
+        range_syntax::parse_range_set(&self.ranges.join(" "), len as u32)
+    }
+}
+
+#[derive(Debug, Parser)]
+pub struct ItemSelector {
+    /// The index, id or pattern of the trash item
+    ///
+    /// A number is interpreted as the index shown in the 'i' column of 'trashy list',
+    /// with the items sorted by '--sort' and '--rev' like there.
+    /// Otherwise the item with the exact id is chosen if there is one.
+    /// Otherwise the argument is matched against the original path of each item,
+    /// using the pattern type specified in '--match'.
+    #[arg(verbatim_doc_comment)]
+    pub item: String,
+
+    /// What type of pattern to use
+    #[arg(short, long, value_enum, default_value_t = Match::Regex)]
+    pub r#match: Match,
+
+    /// How the trash items are sorted when looking up an index
+    ///
+    /// This should be the same as for the 'trashy list' the index was taken from.
+    #[arg(long, value_enum, default_value_t = SortBy::Time)]
+    pub sort: SortBy,
+
+    /// Reverse the sorting of trash items when looking up an index
+    #[arg(long)]
+    pub rev: bool,
+}
+
+impl ItemSelector {
+    /// Returns all items selected, together with their index in 'trashy list' with the same sorting.
+    pub fn select(&self) -> Result<Vec<(u32, TrashItem)>> {
+        let items = list::list(self.sort, self.rev, None, Filters::new(Vec::new()))?;
+        let selected: Vec<_> = if let Ok(i) = self.item.parse::<u32>() {
+            match items.into_iter().nth(i as usize) {
+                Some(item) => vec![(i, item)],
+                None => bail!("Index {i} is out of bounds"),
+            }
+        } else if let Some(i) = items.iter().position(|item| item.id == *self.item) {
+            vec![(i as u32, items.into_iter().nth(i).unwrap())]
+        } else {
+            let patterns = PatternSet::new(self.r#match, iter::once(&self.item), Case::Smart, 0)?;
+            (0..)
+                .zip(items)
+                .filter(|(_, item)| patterns.is_match(&item.original_path().to_string_lossy()))
+                .collect()
+        };
+        if selected.is_empty() {
+            bail!("No trash item matches `{}`", self.item);
+        }
+        Ok(selected)
+    }
+
+    /// Like [`ItemSelector::select`], but fails if more than one item is selected.
+    pub fn select_one(&self) -> Result<(u32, TrashItem)> {
+        let mut selected = self.select()?;
+        if selected.len() > 1 {
+            bail!(
+                "{} trash items match `{}`, use an index or id to choose one",
+                selected.len(),
+                self.item
+            );
+        }
+        Ok(selected.remove(0))
     }
 }
@@ -0,0 +1,448 @@
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::app::command::list::SortBy;
+use crate::app::command::utils::Interactive;
+use crate::app::{Column, ConfigArgs, Status, TimeDisplayMode};
+use crate::filter::Match;
+use crate::{print, utils};
+
+/// The config file, which provides defaults for command line options.
+///
+/// Every option that is left out keeps the default it has without a config file.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// The default of '--color'.
+    pub color: Option<Status>,
+    /// The default of '--time'.
+    pub time: Option<TimeDisplayMode>,
+    /// The default of '--table'.
+    pub table: Option<Status>,
+    /// The default of '--columns'.
+    pub columns: Option<Vec<Column>>,
+    /// The default of '--match'.
+    pub r#match: Option<Match>,
+    /// The default of '--sort'.
+    pub sort: Option<SortBy>,
+    /// The default of '--interactive'.
+    pub interactive: Option<Interactive>,
+    /// Paths that 'trashy put' refuses to trash, neither directly nor by trashing a directory containing them.
+    pub protected: Vec<PathBuf>,
+    /// Commands that expand to other commands and their arguments, which are split like a shell does.
+    pub alias: BTreeMap<String, String>,
+
+    /// The file the config was read from.
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+    /// The environment variables that override options of the file.
+    #[serde(skip)]
+    pub env_vars: Vec<&'static str>,
+}
+
+impl Config {
+    /// Reads the config file chosen by '--config' and '--no-config' in `args`,
+    /// and overrides its options with environment variables.
+    ///
+    /// This has to happen before the arguments are parsed, because the config changes their defaults.
+    /// A missing config file in the default location is not an error.
+    pub fn from_args(args: &[OsString]) -> Result<Config> {
+        let mut config = Config::from_file_in_args(args)?;
+        config.apply_env()?;
+        Ok(config)
+    }
+
+    fn from_file_in_args(args: &[OsString]) -> Result<Config> {
+        let mut path = None;
+        let mut no_config = false;
+        let mut args = args.iter().skip(1);
+        while let Some(arg) = args.next() {
+            if arg == "--" {
+                break;
+            } else if arg == "--no-config" {
+                no_config = true;
+            } else if arg == "--config" {
+                path = args.next().map(PathBuf::from);
+            } else if let Some(p) = arg.to_str().and_then(|arg| arg.strip_prefix("--config=")) {
+                path = Some(PathBuf::from(p));
+            }
+        }
+        if no_config {
+            return Ok(Config::default());
+        }
+        match path
+            .or_else(|| env::var_os("TRASHY_CONFIG").filter(|p| !p.is_empty()).map(PathBuf::from))
+        {
+            Some(path) => Config::load(&path),
+            None => match default_path() {
+                Some(path) if path.exists() => Config::load(&path),
+                _ => Ok(Config::default()),
+            },
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Config> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file `{}`", path.display()))?;
+        let mut config = Config::parse(&contents)
+            .with_context(|| format!("Invalid config file `{}`", path.display()))?;
+        config.path = Some(path.to_path_buf());
+        Ok(config)
+    }
+
+    fn parse(contents: &str) -> Result<Config> {
+        let mut config: Config = toml::from_str(contents)?;
+        for path in &mut config.protected {
+            *path = expand_home(path);
+        }
+        config.check_aliases()?;
+        Ok(config)
+    }
+
+    /// Fails if an alias has the name of a command or doesn't expand to any arguments.
+    fn check_aliases(&self) -> Result<()> {
+        use clap::CommandFactory;
+
+        let cmd = crate::app::Args::command();
+        for (name, expansion) in &self.alias {
+            if cmd.find_subcommand(name).is_some() {
+                bail!("The alias `{name}` has the same name as a command");
+            }
+            if split_alias(name, expansion)?.is_empty() {
+                bail!("The alias `{name}` is empty");
+            }
+        }
+        Ok(())
+    }
+
+    /// Overrides options with the `TRASHY_*` environment variables named after them,
+    /// and the color with `NO_COLOR` or `CLICOLOR_FORCE`.
+    fn apply_env(&mut self) -> Result<()> {
+        let used = &mut self.env_vars;
+        if let Some((use_color, name)) = print::color_from_env() {
+            self.color = Some(if use_color { Status::Always } else { Status::Never });
+            used.push(name);
+        }
+        self.color = env_value("TRASHY_COLOR", used)?.or(self.color);
+        self.time = env_value("TRASHY_TIME", used)?.or(self.time);
+        self.table = env_value("TRASHY_TABLE", used)?.or(self.table);
+        self.columns = env_values("TRASHY_COLUMNS", used)?.or(self.columns.take());
+        self.r#match = env_value("TRASHY_MATCH", used)?.or(self.r#match);
+        self.sort = env_value("TRASHY_SORT", used)?.or(self.sort);
+        self.interactive = env_value("TRASHY_INTERACTIVE", used)?.or(self.interactive);
+        Ok(())
+    }
+
+    /// This config with every option that is left out set to its built-in default.
+    pub fn effective(&self) -> Config {
+        let defaults = ConfigArgs::default();
+        Config {
+            color: Some(self.color.unwrap_or(defaults.color_status)),
+            time: Some(self.time.unwrap_or(defaults.time_display_mode)),
+            table: Some(self.table.unwrap_or(defaults.table_status)),
+            columns: Some(self.columns.clone().unwrap_or(defaults.columns)),
+            r#match: Some(self.r#match.unwrap_or_default()),
+            sort: Some(self.sort.unwrap_or(SortBy::Time)),
+            interactive: Some(self.interactive.unwrap_or(Interactive::Once)),
+            protected: self.protected.clone(),
+            alias: self.alias.clone(),
+            path: self.path.clone(),
+            env_vars: self.env_vars.clone(),
+        }
+    }
+
+    /// Makes the options of the config the defaults of the matching arguments in `cmd` and all of its subcommands.
+    pub fn apply(&self, cmd: clap::Command) -> clap::Command {
+        let mut defaults = Vec::new();
+        let mut add = |id, values: Vec<String>| defaults.push((id, values));
+        if let Some(color) = self.color {
+            add("color_status", vec![value_name(&color)]);
+        }
+        if let Some(time) = self.time {
+            add("time_display_mode", vec![value_name(&time)]);
+        }
+        if let Some(table) = self.table {
+            add("table_status", vec![value_name(&table)]);
+        }
+        if let Some(columns) = &self.columns {
+            add("columns", columns.iter().map(value_name).collect());
+        }
+        if let Some(r#match) = self.r#match {
+            add("match", vec![value_name(&r#match)]);
+        }
+        if let Some(sort) = self.sort {
+            add("sort", vec![value_name(&sort)]);
+        }
+        if let Some(interactive) = self.interactive {
+            add("interactive", vec![value_name(&interactive)]);
+        }
+        let mut cmd = set_defaults(cmd, &defaults);
+        for (name, expansion) in &self.alias {
+            if cmd.find_subcommand(name).is_some() {
+                continue;
+            }
+            // the command that is aliased, so that help and completions know its arguments
+            let aliased = split_alias(name, expansion)
+                .ok()
+                .and_then(|args| cmd.find_subcommand(args.first()?).cloned())
+                .unwrap_or_else(|| clap::Command::new(name.clone()));
+            cmd = cmd.subcommand(
+                aliased
+                    .name(name.clone())
+                    .about(format!("Alias for `{expansion}`"))
+                    .long_about(None),
+            );
+        }
+        cmd
+    }
+
+    /// Replaces the command in `args` with its expansion if it is an alias.
+    ///
+    /// `cmd` is needed to know which options before the command take a value,
+    /// and must not have the aliases applied yet.
+    pub fn expand_alias(
+        &self,
+        cmd: &clap::Command,
+        mut args: Vec<OsString>,
+    ) -> Result<Vec<OsString>> {
+        let i = match command_position(cmd, &args) {
+            Some(i) => i,
+            None => return Ok(args),
+        };
+        let name = match args[i].to_str() {
+            Some(name) => name,
+            None => return Ok(args),
+        };
+        let expansion = match self.alias.get(name) {
+            Some(expansion) => expansion,
+            None => return Ok(args),
+        };
+        let expansion = split_alias(name, expansion)?;
+        args.splice(i..=i, expansion.into_iter().map(OsString::from));
+        Ok(args)
+    }
+
+    /// Fails if trashing `path` would trash one of the protected paths.
+    pub fn ensure_unprotected(&self, path: &Path) -> Result<()> {
+        if self.protected.is_empty() {
+            return Ok(());
+        }
+        let resolved = resolve(path, false)?;
+        for protected in &self.protected {
+            if resolve(protected, true)?.starts_with(&resolved) {
+                bail!(
+                    "Refusing to trash `{}`, because the config protects `{}`",
+                    path.display(),
+                    protected.display()
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Splits the expansion of the alias `name` into arguments, with the quoting and escaping of a shell.
+fn split_alias(name: &str, expansion: &str) -> Result<Vec<String>> {
+    shell_words::split(expansion).with_context(|| format!("Invalid alias `{name}`"))
+}
+
+/// Where the command is in `args`, which is the first argument that is not an option or its value.
+fn command_position(cmd: &clap::Command, args: &[OsString]) -> Option<usize> {
+    let takes_value =
+        |arg: Option<&clap::Arg>| arg.map_or(false, |arg| arg.get_action().takes_values());
+    let mut i = 1;
+    while i < args.len() {
+        let arg = args[i].to_str()?;
+        if arg == "--" {
+            return None;
+        } else if let Some(long) = arg.strip_prefix("--") {
+            if !long.contains('=')
+                && takes_value(cmd.get_arguments().find(|arg| arg.get_long() == Some(long)))
+            {
+                i += 1;
+            }
+        } else if let Some(shorts) = arg.strip_prefix('-').filter(|shorts| !shorts.is_empty()) {
+            // the value is either the rest of the argument or the next one
+            let short_args = shorts.char_indices().map(|(j, c)| {
+                (j + c.len_utf8(), cmd.get_arguments().find(|arg| arg.get_short() == Some(c)))
+            });
+            for (end, short_arg) in short_args {
+                if takes_value(short_arg) {
+                    if end == shorts.len() {
+                        i += 1;
+                    }
+                    break;
+                }
+            }
+        } else {
+            return Some(i);
+        }
+        i += 1;
+    }
+    None
+}
+
+/// The path of the config file that is used when '--config' is not given.
+pub fn default_path() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))
+        .map(|dir| dir.join("trashy").join("config.toml"))
+}
+
+fn set_defaults(mut cmd: clap::Command, defaults: &[(&str, Vec<String>)]) -> clap::Command {
+    for (id, values) in defaults {
+        if cmd.get_arguments().any(|arg| arg.get_id() == id) {
+            cmd = cmd.mut_arg(id, |arg| arg.default_values(values));
+        }
+    }
+    let names: Vec<_> = cmd.get_subcommands().map(|sub| sub.get_name().to_string()).collect();
+    for name in names {
+        cmd = cmd.mut_subcommand(name, |sub| set_defaults(sub, defaults));
+    }
+    cmd
+}
+
+/// Parses the environment variable `name` if it is set and not empty, and records it in `used`.
+fn env_value<T: ValueEnum>(name: &'static str, used: &mut Vec<&'static str>) -> Result<Option<T>> {
+    match env_values(name, used)? {
+        Some(values) if values.len() > 1 => bail!("`{name}` only takes one value"),
+        values => Ok(values.map(|mut values| values.remove(0))),
+    }
+}
+
+/// Like [`env_value`], but for a comma separated list.
+fn env_values<T: ValueEnum>(
+    name: &'static str,
+    used: &mut Vec<&'static str>,
+) -> Result<Option<Vec<T>>> {
+    let value = match env::var(name) {
+        Ok(value) if !value.trim().is_empty() => value,
+        Ok(_) | Err(env::VarError::NotPresent) => return Ok(None),
+        Err(env::VarError::NotUnicode(_)) => bail!("`{name}` is not valid unicode"),
+    };
+    let values = value
+        .split(',')
+        .map(|v| {
+            T::from_str(v.trim(), true).map_err(|_| {
+                let possible: Vec<_> = T::value_variants().iter().map(value_name).collect();
+                anyhow!(
+                    "Invalid value `{v}` in `{name}`, possible values are {}",
+                    possible.join(", ")
+                )
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    used.push(name);
+    Ok(Some(values))
+}
+
+fn value_name<T: ValueEnum>(value: &T) -> String {
+    value.to_possible_value().expect("no skipped values").get_name().to_string()
+}
+
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), env::var_os("HOME")) {
+        (Ok(rest), Some(home)) if rest.as_os_str().is_empty() => PathBuf::from(home),
+        (Ok(rest), Some(home)) => Path::new(&home).join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Makes `path` absolute and resolves symlinks, except for the last component unless `follow` is set,
+/// because trashing a symlink only trashes the link.
+fn resolve(path: &Path, follow: bool) -> Result<PathBuf> {
+    let path = utils::path::normalize(path)?;
+    let resolved = match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) if !follow => {
+            fs::canonicalize(parent).map(|parent| parent.join(name))
+        }
+        _ => fs::canonicalize(&path),
+    };
+    Ok(resolved.unwrap_or(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+
+    #[test]
+    fn parse() {
+        let config = Config::parse(
+            r#"
+            color = "never"
+            columns = ["index", "size", "original-path"]
+            match = "glob"
+            interactive = "each"
+            protected = ["/etc"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.color, Some(Status::Never));
+        assert_eq!(config.columns, Some(vec![Column::Index, Column::Size, Column::Path]));
+        assert_eq!(config.interactive, Some(Interactive::Each));
+        assert_eq!(config.protected, vec![PathBuf::from("/etc")]);
+        assert!(config.time.is_none());
+
+        assert!(Config::parse("colour = \"never\"").is_err());
+        assert!(Config::parse("color = \"sometimes\"").is_err());
+    }
+
+    #[test]
+    fn aliases() {
+        use clap::CommandFactory;
+
+        let config = Config::parse(
+            r#"
+            [alias]
+            rs = "restore --pick"
+            logs = "list --glob '*.log' --ext \"tar gz\""
+            "#,
+        )
+        .unwrap();
+        let cmd = crate::app::Args::command();
+        let expand = |args: &str| {
+            let args = args.split(' ').map(OsString::from).collect();
+            config.expand_alias(&cmd, args).map(|args| args.join(OsStr::new("|")))
+        };
+        assert_eq!(expand("trashy rs").unwrap(), "trashy|restore|--pick");
+        assert_eq!(
+            expand("trashy --time precise -c never rs -r 1").unwrap(),
+            "trashy|--time|precise|-c|never|restore|--pick|-r|1"
+        );
+        assert_eq!(expand("trashy -cnever rs").unwrap(), "trashy|-cnever|restore|--pick");
+        assert_eq!(expand("trashy --no-config rs").unwrap(), "trashy|--no-config|restore|--pick");
+        assert_eq!(expand("trashy restore rs").unwrap(), "trashy|restore|rs");
+        assert_eq!(expand("trashy -- rs").unwrap(), "trashy|--|rs");
+        assert_eq!(expand("trashy logs").unwrap(), "trashy|list|--glob|*.log|--ext|tar gz");
+        assert_eq!(expand("trashy list").unwrap(), "trashy|list");
+
+        for alias in
+            ["list = \"list --sort path\"", "empty = \"list\"", "e = \" \"", "q = \"list '\""]
+        {
+            assert!(
+                Config::parse(&format!("[alias]\n{alias}")).is_err(),
+                "Expected `{alias}` to fail"
+            );
+        }
+    }
+
+    #[test]
+    fn protected() {
+        let config = Config { protected: vec![PathBuf::from("/a/b")], ..Config::default() };
+        assert!(config.ensure_unprotected(Path::new("/a/b")).is_err());
+        assert!(config.ensure_unprotected(Path::new("/a/b/../b")).is_err());
+        assert!(config.ensure_unprotected(Path::new("/a")).is_err());
+        assert!(config.ensure_unprotected(Path::new("/a/b/c")).is_ok());
+        assert!(config.ensure_unprotected(Path::new("/a/bc")).is_ok());
+    }
+}
@@ -7,6 +7,10 @@ use crate::print;
 pub enum ExitCode {
     Success,
     Error,
+    /// The user answered no to a prompt.
+    Declined,
+    /// A prompt was needed, but there was no terminal to show it in.
+    NotInteractive,
     Interrupted,
 }
 
@@ -15,6 +19,8 @@ impl From<ExitCode> for i32 {
         match value {
             ExitCode::Success => 0,
             ExitCode::Error => 1,
+            ExitCode::Declined => 3,
+            ExitCode::NotInteractive => 4,
             ExitCode::Interrupted => 130,
         }
     }
@@ -31,9 +37,12 @@ impl ExitCode {
             ExitCode::Success => {
                 println!("{}", msg);
             }
-            ExitCode::Error => {
+            ExitCode::Error | ExitCode::NotInteractive => {
                 print::err_display(msg);
             }
+            ExitCode::Declined => {
+                eprintln!("{}", msg);
+            }
             ExitCode::Interrupted => {
                 println!("Interrupted")
             }
@@ -44,3 +53,34 @@ impl ExitCode {
         process::exit(self.into())
     }
 }
+
+/// Errors from asking the user something, which exit with their own exit codes.
+#[derive(Debug)]
+pub enum PromptError {
+    /// The message says what could not be asked and how to avoid asking.
+    NotInteractive(String),
+    /// The message says what was not done.
+    Declined(String),
+    Interrupted,
+}
+
+impl PromptError {
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            PromptError::NotInteractive(_) => ExitCode::NotInteractive,
+            PromptError::Declined(_) => ExitCode::Declined,
+            PromptError::Interrupted => ExitCode::Interrupted,
+        }
+    }
+}
+
+impl fmt::Display for PromptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PromptError::NotInteractive(msg) | PromptError::Declined(msg) => f.write_str(msg),
+            PromptError::Interrupted => f.write_str("Interrupted"),
+        }
+    }
+}
+
+impl std::error::Error for PromptError {}
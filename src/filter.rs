@@ -1,24 +1,40 @@
+//! Filters on trash items, built from the filter options of the command line.
+
 use std::{
+    borrow::Cow,
     collections::HashSet,
-    fs,
+    fmt, fs,
     path::{Path, PathBuf},
 };
 
 use aho_corasick::AhoCorasick;
-use anyhow::{anyhow, bail, Result};
-use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
-use clap::{ArgAction, Parser, ValueEnum};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, TimeZone, Utc};
+use clap::{ArgAction, ArgMatches, FromArgMatches, Parser, ValueEnum};
 
-use globset::{Glob, GlobSet, GlobSetBuilder};
-use regex::RegexSet;
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use regex::{RegexSet, RegexSetBuilder};
+use serde::{Deserialize, Serialize};
 use trash::TrashItem;
 
-#[derive(Parser, Debug)]
-pub struct FilterArgs {
+use crate::{time_syntax, trash_item, utils};
+
+mod expr;
+mod query;
+
+use expr::{Primary, Token};
+
+/// The options that filter trash items, as given on the command line.
+///
+/// The default has no filters and the same options as the command line without arguments.
+#[derive(Parser, Debug, Default)]
+pub struct FilterOptions {
     /// Filter by time (older than)
     ///
     /// Filter results based on when the file was trashed. The argument can be provided
-    /// as a specific point in time (YYYY-MM-DD HH:MM:SS) or as a duration (10h, 1d, 35min).
+    /// as a specific point in time (YYYY-MM-DD HH:MM, optionally with :SS), as a day (today, yesterday, last monday)
+    /// or as a duration (10h, 1d, 35min).
     /// '--older-than' or '--older' can be used as aliases.
     /// This option can be used in 'list', 'restore', and 'empty'
     /// Examples:
@@ -31,7 +47,8 @@ pub struct FilterArgs {
     /// Filter by time
     ///
     /// Filter results based on when the file was trashed. The argument can be provided
-    /// as a specific point in time (YYYY-MM-DD HH:MM:SS) or as a duration (10h, 1d, 35min).
+    /// as a specific point in time (YYYY-MM-DD HH:MM, optionally with :SS), as a day (today, yesterday, last monday)
+    /// or as a duration (10h, 1d, 35min).
     /// '--newer-than' or '--newer' can be used as aliases.
     /// This option can be used in 'list', 'restore', and 'empty'
     /// Examples:
@@ -41,6 +58,29 @@ pub struct FilterArgs {
     #[arg(long, visible_alias = "newer-than", visible_alias = "newer", action = ArgAction::Append, verbatim_doc_comment)]
     pub within: Vec<String>,
 
+    /// Filter by a span of time
+    ///
+    /// Only keep items trashed within a span of time. The argument is either a single day,
+    /// or a range 'start..end' where the start is inclusive, the end is exclusive,
+    /// and either end can be left out. Both ends accept the same values as '--before'.
+    /// Examples:
+    ///     --deleted yesterday
+    ///     --deleted 2024-01-01..2024-02-01
+    ///     --deleted 'last monday..'
+    ///     --deleted 1w..1d
+    #[arg(long, action = ArgAction::Append, verbatim_doc_comment)]
+    pub deleted: Vec<String>,
+
+    /// The current time used by relative time filters
+    ///
+    /// Durations, 'today', 'yesterday' and weekdays are relative to this time.
+    /// Setting it makes queries reproducible, for example in scripts.
+    /// It accepts a specific point in time (YYYY-MM-DD HH:MM, optionally with :SS, or RFC 3339).
+    /// Examples:
+    ///     --now '2024-01-17 12:00' --deleted yesterday
+    #[arg(long, verbatim_doc_comment)]
+    pub now: Option<String>,
+
     /// Filter by regex
     #[arg(long, action = ArgAction::Append)]
     pub regex: Vec<String>,
@@ -57,126 +97,635 @@ pub struct FilterArgs {
     #[arg(long, action = ArgAction::Append)]
     pub exact: Vec<String>,
 
+    /// Filter by fuzzy match
+    ///
+    /// The characters of the pattern must appear in order, but not necessarily next to each other.
+    /// Use '--sort score' to show the best matches first.
+    /// Examples:
+    ///     --fuzzy mnrs
+    #[arg(long, action = ArgAction::Append, verbatim_doc_comment)]
+    pub fuzzy: Vec<String>,
+
+    /// Minimum score of fuzzy matches
+    ///
+    /// Fuzzy matches with a lower score than this are filtered out.
+    /// Every matching character adds to the score, with bonuses for consecutive characters
+    /// and characters at the start of words.
+    #[arg(long, default_value_t = 0, verbatim_doc_comment)]
+    pub fuzzy_threshold: i64,
+
     /// Filter by pattern
     ///
     /// This will filter using a pattern type specified in '--match'.
-    /// Using <PATTERNS> and '--match' gives the same effect as passing one of the pattern options explicitly.
+    /// Using 'PATTERNS' and '--match' gives the same effect as passing one of the pattern options explicitly.
     /// So for example
     /// trashy restore '~/projects/**' '~/builds/**' --match=glob
     /// is the same as
     /// trashy restore --glob='~/project/**' --glob='~/builds/**'
+    /// The patterns '(' and ')' are used to group filters, see '--or'.
     #[arg(verbatim_doc_comment)]
     pub patterns: Vec<String>,
 
     /// What type of pattern to use
     ///
-    /// This will choose the pattern type used in <PATTERNS>.
+    /// This will choose the pattern type used in 'PATTERNS'.
     /// Each pattern type has it's own explicit option.
     #[arg(short, long, value_enum, default_value_t = Match::Regex)]
     pub r#match: Match,
 
+    /// What part of the original path patterns are matched against
+    ///
+    /// This applies to 'PATTERNS' and to all of the pattern options.
+    /// Examples:
+    ///     --exact foo.txt --match-on name
+    ///     --match-on parent --glob '**/build'
+    #[arg(long, value_enum, default_value_t = MatchOn::Path, verbatim_doc_comment)]
+    pub match_on: MatchOn,
+
+    /// Case-insensitive search
+    ///
+    /// By default patterns are case-insensitive unless they contain an uppercase character.
+    /// This makes patterns case-insensitive even if they do contain uppercase characters.
+    #[arg(short = 'i', long, overrides_with = "case_sensitive", verbatim_doc_comment)]
+    pub ignore_case: bool,
+
+    /// Case-sensitive search
+    ///
+    /// By default patterns are case-insensitive unless they contain an uppercase character.
+    /// This makes patterns case-sensitive even if they only contain lowercase characters.
+    #[arg(short = 's', long, overrides_with = "ignore_case", verbatim_doc_comment)]
+    pub case_sensitive: bool,
+
     /// Filter by directory
-    #[arg(short = 'd', long = "directory", visible_alias = "dir", action = ArgAction::Append)]
+    ///
+    /// Only show items that were trashed from inside of the directory.
+    /// When given multiple times, items inside of any of the directories are shown.
+    /// The directory does not need to exist anymore.
+    /// Examples:
+    ///     --directory ~/projects
+    ///     --dir src --dir tests --direct
+    #[arg(
+        short = 'd',
+        long = "directory",
+        visible_alias = "dir",
+        action = ArgAction::Append,
+        verbatim_doc_comment
+    )]
     pub directories: Vec<PathBuf>,
+
+    /// Only show items at most this many levels below '--directory'
+    ///
+    /// A depth of 1 means only items that were directly inside of the directory.
+    #[arg(long, value_name = "DEPTH", verbatim_doc_comment)]
+    pub max_depth: Option<usize>,
+
+    /// Only show items directly inside of '--directory'
+    ///
+    /// This is the same as '--max-depth 1'.
+    #[arg(long, conflicts_with = "max_depth", verbatim_doc_comment)]
+    pub direct: bool,
+
+    /// Filter by file type
+    ///
+    /// Filter results based on the type of the trashed file.
+    /// When given multiple times, items matching any of the types are shown.
+    /// Examples:
+    ///     --type dir
+    ///     --type empty --type broken-symlink
+    #[arg(long = "type", value_enum, action = ArgAction::Append, verbatim_doc_comment)]
+    pub file_types: Vec<FileType>,
+
+    /// Filter by size
+    ///
+    /// Filter results based on the size of the trashed file, including the contents of directories.
+    /// The format is a number and a unit, optionally prefixed by '+' for at least or '-' for at most.
+    /// Without '+' or '-' the size must match exactly.
+    /// Allowed units are 'b' (bytes), 'k', 'm', 'g', 't' (powers of 1000)
+    /// and 'ki', 'mi', 'gi', 'ti' (powers of 1024).
+    /// Examples:
+    ///     --size +100M
+    ///     --size -1k
+    ///     --size +1ki --size -10mi
+    #[arg(long, allow_hyphen_values = true, action = ArgAction::Append, verbatim_doc_comment)]
+    pub size: Vec<String>,
+
+    /// Filter by file extension
+    ///
+    /// Only keep items whose name ends with the extension, ignoring case.
+    /// When given multiple times, items with any of the extensions are shown.
+    /// Examples:
+    ///     --ext log
+    ///     --ext .tar.gz --ext zip
+    #[arg(long, value_name = "EXT", action = ArgAction::Append, verbatim_doc_comment)]
+    pub ext: Vec<String>,
+
+    /// Filter by the owning user and/or group of the trashed file
+    ///
+    /// The format is 'USER', 'USER:GROUP' or ':GROUP', where both can be a name or a numeric id.
+    /// Examples:
+    ///     --owner alice
+    ///     --owner :staff
+    ///     --owner 1000:1000
+    #[arg(long, action = ArgAction::Append, verbatim_doc_comment)]
+    pub owner: Vec<String>,
+
+    /// Filter by the permissions of the trashed file
+    ///
+    /// The permissions are given in octal like 'find -perm':
+    ///    'MODE':      the permission bits are exactly MODE
+    ///    '-MODE':     all of the bits in MODE are set
+    ///    '/MODE':     any of the bits in MODE are set
+    /// Examples:
+    ///     --perm 644
+    ///     --perm -100
+    ///     --perm /022
+    #[arg(long, allow_hyphen_values = true, action = ArgAction::Append, verbatim_doc_comment)]
+    pub perm: Vec<String>,
+
+    /// Filter by when the trashed file was last modified (older than)
+    ///
+    /// Unlike '--before', this uses the modification time of the trashed file itself
+    /// instead of when it was trashed. It accepts the same values as '--before'.
+    /// Examples:
+    ///     --modified-before 1y
+    #[arg(long, action = ArgAction::Append, verbatim_doc_comment)]
+    pub modified_before: Vec<String>,
+
+    /// Filter by when the trashed file was last modified (newer than)
+    ///
+    /// Unlike '--within', this uses the modification time of the trashed file itself
+    /// instead of when it was trashed. It accepts the same values as '--within'.
+    /// Examples:
+    ///     --modified-after 2024-01-01
+    #[arg(long, action = ArgAction::Append, verbatim_doc_comment)]
+    pub modified_after: Vec<String>,
+
+    /// Filter with a query
+    ///
+    /// A query compares fields of trash items with values, and combines them with
+    /// 'and', 'or', 'not' and parentheses. Quote values that contain spaces or any of (),"'<>=!~
+    ///    'deleted', 'modified':       <, <=, >, >=, =, != with a time or duration,
+    ///                                 where durations are ages ('deleted < 2d' is less than two days ago)
+    ///                                 and '=' takes a day or a range like '--deleted'
+    ///    'size':                      <, <=, >, >=, =, != with a size like '--size'
+    ///    'type', 'ext', 'owner',
+    ///    'perm', 'dir':               =, !=, in, not in
+    ///    'path', 'name', 'parent':    = (exact), ~ (glob), =~ (regex), !=, !~, in, not in
+    /// Examples:
+    ///     -q 'size > 10M and ext in (log, tmp) and deleted < 2d and not path ~ "**/keep/**"'
+    ///     -q 'type = dir or (name =~ "^build" and modified > 1y)'
+    #[arg(short = 'q', long, action = ArgAction::Append, verbatim_doc_comment)]
+    pub query: Vec<String>,
+
+    /// Negate the next filter
+    ///
+    /// Only keep items that do not match the filter or group right after '--not'.
+    /// Examples:
+    ///     --not --glob '**/keep/**'
+    ///     --not '(' --glob '*.log' --older 1d ')'
+    #[arg(
+        long,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true",
+        hide_possible_values = true,
+        value_parser = parse_operator_value,
+        action = ArgAction::Append,
+        verbatim_doc_comment
+    )]
+    pub not: Vec<bool>,
+
+    /// Match either the filters before or after
+    ///
+    /// By default all filters must match. Filters joined with '--or' only need one side to match.
+    /// Filters next to each other are combined before '--or' is applied,
+    /// so 'A B --or C' matches items matching both A and B, or C.
+    /// Use '(' and ')' as separate arguments to group filters.
+    /// Examples:
+    ///     --glob '**/*.log' --or --older 1w
+    ///     '(' --glob '**/*.log' --or --glob '**/*.tmp' ')' --not --dir ~/keep
+    #[arg(
+        long,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true",
+        hide_possible_values = true,
+        value_parser = parse_operator_value,
+        action = ArgAction::Append,
+        verbatim_doc_comment
+    )]
+    pub or: Vec<bool>,
+}
+
+/// The filter options, together with the order they were given in on the command line.
+///
+/// The order is needed to know which filters '--not', '--or', '(' and ')' apply to.
+#[derive(Debug)]
+pub struct FilterArgs {
+    /// The options the filters are made of.
+    pub options: FilterOptions,
+    order: Vec<(&'static str, usize)>,
 }
 
 impl FilterArgs {
+    /// Filter arguments that are not from a command line.
+    ///
+    /// Without a command line the order of the options is unknown, so filters of different kinds are
+    /// all and-ed together. '--not', '--or' and parentheses can't be used; combine [`Filter`]s instead.
+    pub fn new(options: FilterOptions) -> FilterArgs {
+        FilterArgs { options, order: Vec::new() }
+    }
+
+    /// The ids of the arguments that take part in filter expressions.
+    const EXPR_ARGS: &'static [&'static str] = &[
+        "before",
+        "within",
+        "deleted",
+        "directories",
+        "file_types",
+        "size",
+        "ext",
+        "owner",
+        "perm",
+        "modified_before",
+        "modified_after",
+        "query",
+        "regex",
+        "glob",
+        "substring",
+        "exact",
+        "fuzzy",
+        "patterns",
+        "not",
+        "or",
+    ];
+
+    /// Turns the options into filters, resolving times relative to '--now' or the current time.
     pub fn to_filters(&self) -> Result<Filters> {
-        let now = Utc::now();
-        let parse_time =
-            |s| parse_time_filter(now, s).ok_or_else(|| anyhow!("Invalid duration or date"));
-        let mut filters = Vec::new();
-        if !self.before.is_empty() {
-            filters.extend(
-                self.before
-                    .iter()
-                    .map(|ref s| Ok(Filter::Time(TimeFilter::Before(parse_time(s)?))))
-                    .collect::<Result<Vec<_>>>()?,
-            );
-        }
-        if !self.within.is_empty() {
-            filters.extend(
-                self.within
-                    .iter()
-                    .map(|s| Ok(Filter::Time(TimeFilter::After(parse_time(s)?))))
-                    .collect::<Result<Vec<_>>>()?,
-            );
-        }
-        if !self.directories.is_empty() {
-            let dirs = Filter::Directories(
-                self.directories
-                    .iter()
-                    .map(|p| {
-                        if !p.is_dir() {
-                            bail!("Path must be a directory");
-                        };
-                        Ok(fs::canonicalize(p)?)
-                    })
-                    .collect::<Result<Vec<_>>>()?,
-            );
-            filters.push(dirs);
-        }
-        if !self.regex.is_empty() {
-            filters.push(Filter::PatternSet(PatternSet::new_regex(self.regex.iter())?));
-        }
-        if !self.glob.is_empty() {
-            filters.push(Filter::PatternSet(PatternSet::new_glob(self.glob.iter())?));
-        }
-        if !self.substring.is_empty() {
-            filters.push(Filter::PatternSet(PatternSet::new_substring(self.substring.iter())));
-        }
-        if !self.exact.is_empty() {
-            filters.push(Filter::PatternSet(PatternSet::new_exact(self.exact.iter())));
-        }
-        if !self.patterns.is_empty() {
-            filters.push(Filter::PatternSet(match self.r#match {
-                Match::Regex => PatternSet::new_regex(self.patterns.iter())?,
-                Match::Substring => PatternSet::new_substring(self.patterns.iter()),
-                Match::Glob => PatternSet::new_glob(self.patterns.iter())?,
-                Match::Exact => PatternSet::new_exact(self.patterns.iter()),
-            }));
-        }
-        Ok(Filters(filters))
+        let now = self.now()?;
+        Ok(Filters(expr::parse(self.tokens(), &|primaries| {
+            conjunction(&self.options, now, primaries)
+        })?))
+    }
+
+    /// The time given by '--now', or the current time.
+    pub fn now(&self) -> Result<DateTime<Utc>> {
+        match &self.options.now {
+            Some(now) => time_syntax::parse_absolute_time(now).context("Invalid `--now`"),
+            None => Ok(Utc::now()),
+        }
+    }
+
+    fn tokens(&self) -> Vec<Token<'_>> {
+        if self.order.is_empty() {
+            Self::EXPR_ARGS
+                .iter()
+                .flat_map(|id| (0..self.options.values_len(id)).map(move |k| self.token(id, k)))
+                .collect()
+        } else {
+            self.order.iter().map(|(id, k)| self.token(id, *k)).collect()
+        }
+    }
+
+    fn token<'a>(&'a self, id: &'static str, k: usize) -> Token<'a> {
+        let options = &self.options;
+        let pattern = |r#match, patterns: &'a [String]| {
+            Token::Primary(Primary::Pattern { id, r#match, pattern: &patterns[k] })
+        };
+        match id {
+            "before" => Token::Primary(Primary::Before(&options.before[k])),
+            "within" => Token::Primary(Primary::Within(&options.within[k])),
+            "deleted" => Token::Primary(Primary::Deleted(&options.deleted[k])),
+            "directories" => Token::Primary(Primary::Directory(&options.directories[k])),
+            "file_types" => Token::Primary(Primary::FileType(options.file_types[k])),
+            "size" => Token::Primary(Primary::Size(&options.size[k])),
+            "ext" => Token::Primary(Primary::Extension(&options.ext[k])),
+            "owner" => Token::Primary(Primary::Owner(&options.owner[k])),
+            "perm" => Token::Primary(Primary::Permissions(&options.perm[k])),
+            "modified_before" => {
+                Token::Primary(Primary::ModifiedBefore(&options.modified_before[k]))
+            }
+            "modified_after" => Token::Primary(Primary::ModifiedAfter(&options.modified_after[k])),
+            "query" => Token::Primary(Primary::Query(&options.query[k])),
+            "regex" => pattern(Match::Regex, &options.regex),
+            "glob" => pattern(Match::Glob, &options.glob),
+            "substring" => pattern(Match::Substring, &options.substring),
+            "exact" => pattern(Match::Exact, &options.exact),
+            "fuzzy" => pattern(Match::Fuzzy, &options.fuzzy),
+            "patterns" => match options.patterns[k].as_str() {
+                "(" => Token::Open,
+                ")" => Token::Close,
+                _ => pattern(options.r#match, &options.patterns),
+            },
+            "not" => Token::Not,
+            "or" => Token::Or,
+            _ => unreachable!("BUG: unknown filter argument `{id}`"),
+        }
+    }
+}
+
+impl FilterOptions {
+    /// The maximum depth below '--directory', taking '--direct' into account.
+    pub fn max_depth(&self) -> Option<usize> {
+        if self.direct {
+            Some(1)
+        } else {
+            self.max_depth
+        }
+    }
+
+    /// How patterns treat case, according to '--ignore-case' and '--case-sensitive'.
+    pub fn case(&self) -> Case {
+        if self.ignore_case {
+            Case::Insensitive
+        } else if self.case_sensitive {
+            Case::Sensitive
+        } else {
+            Case::Smart
+        }
+    }
+
+    fn values_len(&self, id: &str) -> usize {
+        match id {
+            "before" => self.before.len(),
+            "within" => self.within.len(),
+            "deleted" => self.deleted.len(),
+            "directories" => self.directories.len(),
+            "file_types" => self.file_types.len(),
+            "size" => self.size.len(),
+            "ext" => self.ext.len(),
+            "owner" => self.owner.len(),
+            "perm" => self.perm.len(),
+            "modified_before" => self.modified_before.len(),
+            "modified_after" => self.modified_after.len(),
+            "query" => self.query.len(),
+            "regex" => self.regex.len(),
+            "glob" => self.glob.len(),
+            "substring" => self.substring.len(),
+            "exact" => self.exact.len(),
+            "fuzzy" => self.fuzzy.len(),
+            "patterns" => self.patterns.len(),
+            "not" => self.not.len(),
+            "or" => self.or.len(),
+            _ => unreachable!("BUG: unknown filter argument `{id}`"),
+        }
     }
 }
 
+impl FromArgMatches for FilterArgs {
+    fn from_arg_matches(matches: &ArgMatches) -> Result<Self, clap::Error> {
+        Ok(FilterArgs {
+            options: FilterOptions::from_arg_matches(matches)?,
+            order: arg_order(matches),
+        })
+    }
+
+    fn update_from_arg_matches(&mut self, matches: &ArgMatches) -> Result<(), clap::Error> {
+        self.options.update_from_arg_matches(matches)?;
+        self.order = arg_order(matches);
+        Ok(())
+    }
+}
+
+impl clap::Args for FilterArgs {
+    fn augment_args(cmd: clap::Command) -> clap::Command {
+        FilterOptions::augment_args(cmd)
+    }
+
+    fn augment_args_for_update(cmd: clap::Command) -> clap::Command {
+        FilterOptions::augment_args_for_update(cmd)
+    }
+}
+
+/// Parses the value of '--not' and '--or', which only take one so that their positions are recorded.
+fn parse_operator_value(s: &str) -> Result<bool, String> {
+    match s {
+        "true" => Ok(true),
+        _ => Err("this option does not take a value".to_string()),
+    }
+}
+
+/// Returns the id of each filter argument value along with its position among the values of
+/// that argument, sorted by where they appeared on the command line.
+fn arg_order(matches: &ArgMatches) -> Vec<(&'static str, usize)> {
+    let mut order: Vec<_> = FilterArgs::EXPR_ARGS
+        .iter()
+        .flat_map(|&id| {
+            matches.indices_of(id).into_iter().flatten().enumerate().map(move |(k, i)| (i, id, k))
+        })
+        .collect();
+    order.sort_unstable();
+    order.into_iter().map(|(_, id, k)| (id, k)).collect()
+}
+
+/// Turns filter arguments that are and-ed together into filters.
+///
+/// Patterns given with the same argument form a single set that matches if any pattern matches.
+/// Directories, file types and extensions are also combined.
+fn conjunction(
+    options: &FilterOptions,
+    now: DateTime<Utc>,
+    primaries: Vec<Primary<'_>>,
+) -> Result<Vec<Filter>> {
+    let parse_time = |s| time_syntax::parse_time(now, s);
+    let mut filters = Vec::new();
+    let mut directories = Vec::new();
+    let mut file_types = Vec::new();
+    let mut extensions = Vec::new();
+    let mut pattern_sets: Vec<(&str, Match, Vec<&str>)> = Vec::new();
+    for primary in primaries {
+        match primary {
+            Primary::Before(s) => filters.push(Filter::Time(TimeFilter::Before(parse_time(s)?))),
+            Primary::Within(s) => filters.push(Filter::Time(TimeFilter::After(parse_time(s)?))),
+            Primary::Deleted(s) => {
+                let (start, end) = time_syntax::parse_time_span(now, s)?;
+                filters.push(Filter::Time(TimeFilter::Between(start, end)));
+            }
+            Primary::Size(s) => filters.push(Filter::Size(parse_size_filter(s)?)),
+            Primary::Extension(ext) => extensions.push(ext.trim_start_matches('.').to_lowercase()),
+            Primary::Owner(s) => filters.push(Filter::Owner(parse_owner_filter(s)?)),
+            Primary::Permissions(s) => filters.push(Filter::Permissions(parse_perm_filter(s)?)),
+            Primary::ModifiedBefore(s) => {
+                filters.push(Filter::Modified(TimeFilter::Before(parse_time(s)?)))
+            }
+            Primary::ModifiedAfter(s) => {
+                filters.push(Filter::Modified(TimeFilter::After(parse_time(s)?)))
+            }
+            Primary::Directory(p) => directories.push(resolve_directory(p)?),
+            Primary::Query(s) => {
+                filters.push(query::parse(s, now, options.case()).context("Invalid query")?)
+            }
+            Primary::FileType(file_type) => file_types.push(file_type),
+            Primary::Pattern { id, r#match, pattern } => {
+                match pattern_sets.iter_mut().find(|(set_id, _, _)| *set_id == id) {
+                    Some((_, _, patterns)) => patterns.push(pattern),
+                    None => pattern_sets.push((id, r#match, vec![pattern])),
+                }
+            }
+        }
+    }
+    if !directories.is_empty() {
+        filters.push(Filter::Directories(directories, options.max_depth()));
+    }
+    if !file_types.is_empty() {
+        filters.push(Filter::FileTypes(file_types));
+    }
+    if !extensions.is_empty() {
+        filters.push(Filter::Extensions(extensions));
+    }
+    for (_, r#match, patterns) in pattern_sets {
+        filters.push(Filter::PatternSet(
+            PatternSet::new(
+                r#match,
+                patterns.into_iter(),
+                options.case(),
+                options.fuzzy_threshold,
+            )?,
+            options.match_on,
+        ));
+    }
+    Ok(filters)
+}
+
+/// Makes a directory absolute so it can be compared to original paths.
+/// The directory does not need to exist anymore.
+fn resolve_directory(p: &Path) -> Result<PathBuf> {
+    if p.exists() && !p.is_dir() {
+        bail!("`{}` is not a directory", p.display());
+    };
+    Ok(match fs::canonicalize(p) {
+        Ok(p) => p,
+        Err(_) => utils::path::normalize(p)?,
+    })
+}
+
+/// Filters that must all match.
 pub struct Filters(pub Vec<Filter>);
 
 impl Filters {
+    /// Filters that match the items all of `filters` match.
+    pub fn new(filters: Vec<Filter>) -> Filters {
+        Filters(filters)
+    }
+
+    /// Whether all filters match `item`, which is true if there are none.
     pub fn is_match(&self, item: &TrashItem) -> bool {
         self.0.iter().all(|filter| filter.is_match(item))
     }
 
+    /// Whether there are no filters, so that every item matches.
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
     }
+
+    /// How well an item matches the fuzzy patterns, higher is better.
+    pub fn score(&self, item: &TrashItem) -> i64 {
+        self.0.iter().map(|filter| filter.score(item)).sum()
+    }
 }
 
+/// A filter on trash items, which may look at the trashed file itself.
 #[derive(Debug)]
 pub enum Filter {
-    PatternSet(PatternSet),
+    /// Matches the part of the original path chosen by [`MatchOn`] against any of the patterns.
+    PatternSet(PatternSet, MatchOn),
+    /// Matches the time the item was trashed.
     Time(TimeFilter),
-    Directories(Vec<PathBuf>),
+    /// Matches items inside of any of the directories, optionally limited to a maximum depth.
+    Directories(Vec<PathBuf>, Option<usize>),
+    /// Matches trashed files of any of the types.
+    FileTypes(Vec<FileType>),
+    /// Matches the size of the trashed file, including the contents of directories.
+    Size(SizeFilter),
+    /// Lowercased extensions without the leading dot, any of which must match.
+    Extensions(Vec<String>),
+    /// Matches the owning user and group of the trashed file.
+    Owner(OwnerFilter),
+    /// Matches the permission bits of the trashed file.
+    Permissions(PermFilter),
+    /// Matches the modification time of the trashed file.
+    Modified(TimeFilter),
+    /// Matches if all of the filters match.
+    And(Vec<Filter>),
+    /// Matches if any of the filters match.
+    Or(Vec<Filter>),
+    /// Matches if the filter doesn't match.
+    Not(Box<Filter>),
 }
 
 impl Filter {
+    /// Parses a query in the syntax of '--query', where relative times are relative to `now`.
+    pub fn parse_query(query: &str, now: DateTime<Utc>, case: Case) -> Result<Filter> {
+        query::parse(query, now, case)
+    }
+
+    /// Whether `item` matches, where filters on the trashed file don't match if it's missing.
     pub fn is_match(&self, item: &TrashItem) -> bool {
         match self {
-            Filter::PatternSet(patterns) => {
-                patterns.is_match(&item.original_path().to_string_lossy())
+            Filter::PatternSet(patterns, match_on) => patterns.is_match(&match_on.target(item)),
+            Filter::Time(time_filter) => {
+                time_filter.is_match(Utc.timestamp_opt(item.time_deleted, 0).unwrap())
+            }
+            Filter::Directories(directories, max_depth) => {
+                let path = item.original_path();
+                directories.iter().any(|dir| match path.strip_prefix(dir) {
+                    Ok(rest) => max_depth.map_or(true, |max| rest.components().count() <= max),
+                    Err(_) => false,
+                })
+            }
+            Filter::FileTypes(file_types) => match trash_item::payload_path(item) {
+                Some(path) => file_types.iter().any(|file_type| file_type.is_match(&path)),
+                None => false,
+            },
+            Filter::Size(size_filter) => {
+                trash_item::payload_size(item).map_or(false, |size| size_filter.is_match(size))
+            }
+            Filter::Extensions(extensions) => {
+                let name = item.name.to_lowercase();
+                // the name must have a stem, so '.log' does not have the extension 'log'
+                extensions.iter().any(|ext| match name.strip_suffix(ext.as_str()) {
+                    Some(stem) => stem.len() > 1 && stem.ends_with('.'),
+                    None => false,
+                })
+            }
+            Filter::Owner(owner_filter) => trash_item::payload_metadata(item)
+                .map_or(false, |meta| owner_filter.is_match(&meta)),
+            Filter::Permissions(perm_filter) => {
+                trash_item::payload_metadata(item).map_or(false, |meta| perm_filter.is_match(&meta))
+            }
+            Filter::Modified(time_filter) => trash_item::payload_metadata(item)
+                .and_then(|meta| meta.modified().ok())
+                .map_or(false, |modified| time_filter.is_match(modified.into())),
+            Filter::And(filters) => filters.iter().all(|filter| filter.is_match(item)),
+            Filter::Or(filters) => filters.iter().any(|filter| filter.is_match(item)),
+            Filter::Not(filter) => !filter.is_match(item),
+        }
+    }
+
+    /// How well an item matches the fuzzy patterns of this filter, other filters score 0.
+    pub fn score(&self, item: &TrashItem) -> i64 {
+        match self {
+            Filter::PatternSet(PatternSet::Fuzzy(fuzzy), match_on) => {
+                fuzzy.score(&match_on.target(item)).unwrap_or(0)
             }
-            Filter::Time(time_filter) => time_filter.is_match(Utc.timestamp(item.time_deleted, 0)),
-            Filter::Directories(directories) => {
-                directories.iter().all(|p| item.original_path().starts_with(p))
+            Filter::And(filters) => filters.iter().map(|filter| filter.score(item)).sum(),
+            Filter::Or(filters) => {
+                filters.iter().map(|filter| filter.score(item)).max().unwrap_or(0)
             }
+            _ => 0,
         }
     }
 }
 
+/// Matches a point in time.
 #[derive(Debug)]
 pub enum TimeFilter {
+    /// Matches times before the limit.
     Before(DateTime<Utc>),
+    /// Matches times after the limit.
     After(DateTime<Utc>),
+    /// The start is inclusive and the end is exclusive. Missing ends are unbounded.
+    Between(Option<DateTime<Utc>>, Option<DateTime<Utc>>),
 }
 
 impl TimeFilter {
@@ -184,72 +733,612 @@ impl TimeFilter {
         match self {
             TimeFilter::Before(limit) => datetime < *limit,
             TimeFilter::After(limit) => datetime > *limit,
+            TimeFilter::Between(start, end) => {
+                start.map_or(true, |start| datetime >= start)
+                    && end.map_or(true, |end| datetime < end)
+            }
+        }
+    }
+}
+
+/// Matches a size in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeFilter {
+    /// Matches sizes of at least the limit.
+    Min(u64),
+    /// Matches sizes of at most the limit.
+    Max(u64),
+    /// Matches exactly the size.
+    Equals(u64),
+}
+
+impl SizeFilter {
+    fn is_match(self, size: u64) -> bool {
+        match self {
+            SizeFilter::Min(limit) => size >= limit,
+            SizeFilter::Max(limit) => size <= limit,
+            SizeFilter::Equals(limit) => size == limit,
         }
     }
 }
 
+/// Matches the owning user and group, where `None` matches any.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OwnerFilter {
+    /// The id of the user.
+    pub uid: Option<u32>,
+    /// The id of the group.
+    pub gid: Option<u32>,
+}
+
+impl OwnerFilter {
+    #[cfg(unix)]
+    fn is_match(self, meta: &fs::Metadata) -> bool {
+        use std::os::unix::fs::MetadataExt;
+        self.uid.map_or(true, |uid| meta.uid() == uid)
+            && self.gid.map_or(true, |gid| meta.gid() == gid)
+    }
+
+    #[cfg(not(unix))]
+    fn is_match(self, _meta: &fs::Metadata) -> bool {
+        false
+    }
+}
+
+/// Matches permission bits like 'find -perm'.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PermFilter {
+    /// The permission bits are exactly these.
+    Exact(u32),
+    /// All of these bits are set.
+    All(u32),
+    /// Any of these bits are set, or there are no bits to check.
+    Any(u32),
+}
+
+impl PermFilter {
+    #[cfg(unix)]
+    fn is_match(self, meta: &fs::Metadata) -> bool {
+        use std::os::unix::fs::PermissionsExt;
+        self.is_match_mode(meta.permissions().mode() & 0o7777)
+    }
+
+    #[cfg(not(unix))]
+    fn is_match(self, _meta: &fs::Metadata) -> bool {
+        false
+    }
+
+    fn is_match_mode(self, mode: u32) -> bool {
+        match self {
+            PermFilter::Exact(bits) => mode == bits,
+            PermFilter::All(bits) => mode & bits == bits,
+            PermFilter::Any(bits) => bits == 0 || mode & bits != 0,
+        }
+    }
+}
+
+/// The type of a trashed file.
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq, Eq)]
+pub enum FileType {
+    /// Regular files.
+    File,
+    /// Directories.
+    Dir,
+    /// Symbolic links.
+    Symlink,
+    /// Symbolic links whose target doesn't exist.
+    BrokenSymlink,
+    /// Empty files and empty directories.
+    Empty,
+}
+
+impl FileType {
+    fn is_match(self, path: &Path) -> bool {
+        let meta = match fs::symlink_metadata(path) {
+            Ok(meta) => meta,
+            Err(_) => return false,
+        };
+        match self {
+            FileType::File => meta.is_file(),
+            FileType::Dir => meta.is_dir(),
+            FileType::Symlink => meta.file_type().is_symlink(),
+            FileType::BrokenSymlink => meta.file_type().is_symlink() && fs::metadata(path).is_err(),
+            FileType::Empty => {
+                if meta.is_dir() {
+                    fs::read_dir(path).map_or(false, |mut entries| entries.next().is_none())
+                } else {
+                    meta.is_file() && meta.len() == 0
+                }
+            }
+        }
+    }
+}
+
+/// Patterns of one type, any of which must match.
 #[derive(Debug)]
 pub enum PatternSet {
+    /// Regular expressions that match anywhere in the string.
     Regex(RegexSet),
-    Substring(Box<AhoCorasick>),
+    /// Substrings, where the bool is whether the string is lowercased before matching.
+    Substring(Box<AhoCorasick>, bool),
+    /// Globs that match the whole string.
     Glob(GlobSet),
-    Exact(HashSet<String>),
+    /// Exact strings, where the bool is whether the string is lowercased before matching.
+    Exact(HashSet<String>, bool),
+    /// Fuzzy patterns.
+    Fuzzy(Box<FuzzySet>),
 }
 
 impl PatternSet {
-    fn is_match(&self, s: &str) -> bool {
+    /// Whether any of the patterns match `s`.
+    pub fn is_match(&self, s: &str) -> bool {
         match self {
             PatternSet::Regex(re_set) => re_set.is_match(s),
-            PatternSet::Substring(ac) => ac.is_match(s),
+            PatternSet::Substring(ac, ignore_case) => ac.is_match(&*fold_case(s, *ignore_case)),
             PatternSet::Glob(glob) => glob.is_match(Path::new(s)),
-            PatternSet::Exact(set) => set.contains(s),
+            PatternSet::Exact(set, ignore_case) => set.contains(&*fold_case(s, *ignore_case)),
+            PatternSet::Fuzzy(fuzzy) => fuzzy.score(s).is_some(),
         }
     }
 
-    fn new_regex(patterns: impl Iterator<Item = impl AsRef<str>>) -> Result<PatternSet> {
-        Ok(PatternSet::Regex(RegexSet::new(patterns)?))
+    /// Builds the `patterns` as the pattern type `match`, where `case` decides if case is ignored.
+    /// `fuzzy_threshold` is the minimum score of fuzzy matches.
+    pub fn new(
+        r#match: Match,
+        patterns: impl Iterator<Item = impl AsRef<str>>,
+        case: Case,
+        fuzzy_threshold: i64,
+    ) -> Result<PatternSet> {
+        let patterns: Vec<_> = patterns.collect();
+        let ignore_case = match case {
+            Case::Sensitive => false,
+            Case::Insensitive => true,
+            Case::Smart => !patterns.iter().any(|s| {
+                let s = s.as_ref();
+                match r#match {
+                    Match::Regex => regex_has_uppercase(s),
+                    _ => s.chars().any(char::is_uppercase),
+                }
+            }),
+        };
+        let patterns = patterns.iter().map(AsRef::as_ref);
+        Ok(match r#match {
+            Match::Regex => PatternSet::new_regex(patterns, ignore_case)?,
+            Match::Substring => PatternSet::new_substring(patterns, ignore_case),
+            Match::Glob => PatternSet::new_glob(patterns, ignore_case)?,
+            Match::Exact => PatternSet::new_exact(patterns, ignore_case),
+            Match::Fuzzy => {
+                PatternSet::Fuzzy(Box::new(FuzzySet::new(patterns, ignore_case, fuzzy_threshold)))
+            }
+        })
+    }
+
+    fn new_regex<'a>(
+        patterns: impl Iterator<Item = &'a str>,
+        ignore_case: bool,
+    ) -> Result<PatternSet> {
+        Ok(PatternSet::Regex(RegexSetBuilder::new(patterns).case_insensitive(ignore_case).build()?))
     }
 
-    fn new_substring(patterns: impl Iterator<Item = impl AsRef<[u8]>>) -> PatternSet {
-        PatternSet::Substring(Box::new(AhoCorasick::new(patterns)))
+    fn new_substring<'a>(patterns: impl Iterator<Item = &'a str>, ignore_case: bool) -> PatternSet {
+        let ac = if ignore_case {
+            AhoCorasick::new(patterns.map(str::to_lowercase))
+        } else {
+            AhoCorasick::new(patterns)
+        };
+        PatternSet::Substring(Box::new(ac), ignore_case)
     }
 
-    fn new_glob(patterns: impl Iterator<Item = impl AsRef<str>>) -> Result<PatternSet> {
+    fn new_glob<'a>(
+        patterns: impl Iterator<Item = &'a str>,
+        ignore_case: bool,
+    ) -> Result<PatternSet> {
         let mut builder = GlobSetBuilder::new();
         for s in patterns {
-            builder.add(Glob::new(s.as_ref())?);
+            builder.add(GlobBuilder::new(s).case_insensitive(ignore_case).build()?);
         }
         Ok(PatternSet::Glob(builder.build()?))
     }
 
-    fn new_exact(patterns: impl Iterator<Item = impl AsRef<str>>) -> PatternSet {
-        PatternSet::Exact(patterns.map(|s| String::from(s.as_ref())).collect())
+    fn new_exact<'a>(patterns: impl Iterator<Item = &'a str>, ignore_case: bool) -> PatternSet {
+        let patterns = patterns.map(|s| if ignore_case { s.to_lowercase() } else { s.to_string() });
+        PatternSet::Exact(patterns.collect(), ignore_case)
+    }
+}
+
+/// Fuzzy patterns, whose characters must appear in order, but not necessarily next to each other.
+pub struct FuzzySet {
+    matcher: SkimMatcherV2,
+    patterns: Vec<String>,
+    threshold: i64,
+}
+
+impl FuzzySet {
+    fn new<'a>(patterns: impl Iterator<Item = &'a str>, ignore_case: bool, threshold: i64) -> Self {
+        let matcher = SkimMatcherV2::default();
+        let matcher = if ignore_case { matcher.ignore_case() } else { matcher.respect_case() };
+        FuzzySet { matcher, patterns: patterns.map(String::from).collect(), threshold }
+    }
+
+    /// The score of the best matching pattern, if it reaches the threshold.
+    pub fn score(&self, s: &str) -> Option<i64> {
+        self.patterns
+            .iter()
+            .filter_map(|pattern| self.matcher.fuzzy_match(s, pattern))
+            .max()
+            .filter(|score| *score >= self.threshold)
+    }
+}
+
+impl fmt::Debug for FuzzySet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FuzzySet")
+            .field("patterns", &self.patterns)
+            .field("threshold", &self.threshold)
+            .finish()
+    }
+}
+
+fn fold_case(s: &str, ignore_case: bool) -> Cow<'_, str> {
+    if ignore_case {
+        Cow::Owned(s.to_lowercase())
+    } else {
+        Cow::Borrowed(s)
+    }
+}
+
+/// Whether a regex contains an uppercase character outside of escape sequences like `\W`.
+fn regex_has_uppercase(pattern: &str) -> bool {
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            chars.next();
+        } else if c.is_uppercase() {
+            return true;
+        }
+    }
+    false
+}
+
+/// How patterns treat case.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Case {
+    /// Case-sensitive.
+    Sensitive,
+    /// Case-insensitive.
+    Insensitive,
+    /// Case-insensitive, unless a pattern contains an uppercase character.
+    Smart,
+}
+
+/// The part of the original path that patterns are matched against.
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MatchOn {
+    /// The full original path of the item.
+    #[default]
+    Path,
+    /// The file name of the item.
+    Name,
+    /// The directory the item was trashed from.
+    Parent,
+}
+
+impl MatchOn {
+    fn target(self, item: &TrashItem) -> Cow<'_, str> {
+        match self {
+            MatchOn::Path => Cow::Owned(item.original_path().to_string_lossy().into_owned()),
+            MatchOn::Name => Cow::Borrowed(&item.name),
+            MatchOn::Parent => item.original_parent.to_string_lossy(),
+        }
     }
 }
 
-#[derive(Debug, ValueEnum, Clone, Copy)]
+/// The type of a pattern.
+#[derive(Debug, ValueEnum, Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "kebab-case")]
 pub enum Match {
+    /// Regular expressions, like '--regex'.
+    #[default]
     Regex,
+    /// Substrings, like '--substring'.
     Substring,
+    /// Globs, like '--glob'.
     Glob,
+    /// Exact strings, like '--exact'.
     Exact,
+    /// Fuzzy patterns, like '--fuzzy'.
+    Fuzzy,
 }
 
-fn parse_time_filter(ref_time: DateTime<Utc>, s: &str) -> Option<DateTime<Utc>> {
-    humantime::parse_duration(s)
-        .ok()
-        .and_then(|duration| Some(ref_time - chrono::Duration::from_std(duration).ok()?))
-        .or_else(|| {
-            DateTime::parse_from_rfc3339(s)
-                .map(|dt| dt.into())
-                .ok()
-                .or_else(|| {
-                    NaiveDate::parse_from_str(s, "%F")
-                        .map(|nd| nd.and_hms(0, 0, 0))
-                        .ok()
-                        .and_then(|ndt| Local.from_local_datetime(&ndt).single())
-                })
-                .or_else(|| Local.datetime_from_str(s, "%F %T").ok())
-                .map(|dt| dt.into())
-        })
+#[cfg(unix)]
+fn parse_owner_filter(s: &str) -> Result<OwnerFilter> {
+    let (user, group) = match s.split_once(':') {
+        Some((user, group)) => (user, Some(group)),
+        None => (s, None),
+    };
+    let uid = match user {
+        "" => None,
+        user => Some(match user.parse() {
+            Ok(uid) => uid,
+            Err(_) => user_id(user).with_context(|| format!("Unknown user `{user}`"))?,
+        }),
+    };
+    let gid = match group {
+        None | Some("") => None,
+        Some(group) => Some(match group.parse() {
+            Ok(gid) => gid,
+            Err(_) => group_id(group).with_context(|| format!("Unknown group `{group}`"))?,
+        }),
+    };
+    if uid.is_none() && gid.is_none() {
+        bail!("Invalid owner `{s}`, expected a user and/or group like `user:group`");
+    }
+    Ok(OwnerFilter { uid, gid })
+}
+
+/// Looks up the id of the user called `name` in the user database.
+#[cfg(unix)]
+fn user_id(name: &str) -> Option<u32> {
+    // SAFETY: the pointers passed by `lookup` are valid for the call
+    let passwd: libc::passwd = lookup(name, |name, entry, buf, len, result| unsafe {
+        libc::getpwnam_r(name, entry, buf, len, result)
+    })?;
+    Some(passwd.pw_uid)
+}
+
+/// Looks up the id of the group called `name` in the group database.
+#[cfg(unix)]
+fn group_id(name: &str) -> Option<u32> {
+    // SAFETY: the pointers passed by `lookup` are valid for the call
+    let group: libc::group = lookup(name, |name, entry, buf, len, result| unsafe {
+        libc::getgrnam_r(name, entry, buf, len, result)
+    })?;
+    Some(group.gr_gid)
+}
+
+/// Calls a reentrant lookup function like `getpwnam_r`, growing the buffer for its strings as needed.
+///
+/// Only the plain fields of the returned entry may be used, as its strings point into the freed buffer.
+#[cfg(unix)]
+fn lookup<T>(
+    name: &str,
+    f: impl Fn(*const libc::c_char, *mut T, *mut libc::c_char, usize, *mut *mut T) -> libc::c_int,
+) -> Option<T> {
+    let name = std::ffi::CString::new(name).ok()?;
+    let mut buf = vec![0; 1024];
+    loop {
+        let mut entry = std::mem::MaybeUninit::<T>::uninit();
+        let mut result = std::ptr::null_mut();
+        match f(name.as_ptr(), entry.as_mut_ptr(), buf.as_mut_ptr(), buf.len(), &mut result) {
+            libc::ERANGE if buf.len() < 1 << 20 => buf.resize(buf.len() * 2, 0),
+            // SAFETY: a non-null result points to `entry`, which was filled in
+            0 if !result.is_null() => return Some(unsafe { entry.assume_init() }),
+            _ => return None,
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn parse_owner_filter(_s: &str) -> Result<OwnerFilter> {
+    bail!("Filtering by owner is only supported on unix")
+}
+
+fn parse_perm_filter(s: &str) -> Result<PermFilter> {
+    if !cfg!(unix) {
+        bail!("Filtering by permissions is only supported on unix");
+    }
+    let (constructor, rest): (fn(u32) -> PermFilter, _) = match s.as_bytes().first() {
+        Some(b'-') => (PermFilter::All, &s[1..]),
+        Some(b'/') => (PermFilter::Any, &s[1..]),
+        _ => (PermFilter::Exact, s),
+    };
+    match u32::from_str_radix(rest, 8) {
+        Ok(bits) if !rest.starts_with('+') && bits <= 0o7777 => Ok(constructor(bits)),
+        _ => bail!("Invalid permissions `{s}`, expected an octal mode like `644`"),
+    }
+}
+
+fn parse_size_filter(s: &str) -> Result<SizeFilter> {
+    let (constructor, rest): (fn(u64) -> SizeFilter, _) = match s.as_bytes().first() {
+        Some(b'+') => (SizeFilter::Min, &s[1..]),
+        Some(b'-') => (SizeFilter::Max, &s[1..]),
+        _ => (SizeFilter::Equals, s),
+    };
+    let split = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+    let (num, unit) = rest.split_at(split);
+    let num = num.parse::<u64>().with_context(|| format!("Invalid size `{s}`"))?;
+    let multiplier: u64 = match unit.to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" => 1000,
+        "m" => 1000u64.pow(2),
+        "g" => 1000u64.pow(3),
+        "t" => 1000u64.pow(4),
+        "ki" => 1024,
+        "mi" => 1024u64.pow(2),
+        "gi" => 1024u64.pow(3),
+        "ti" => 1024u64.pow(4),
+        _ => bail!("Invalid size unit `{unit}` in `{s}`"),
+    };
+    let size = num.checked_mul(multiplier).ok_or_else(|| anyhow!("Size `{s}` is too large"))?;
+    Ok(constructor(size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter_args(args: &[&str]) -> Result<FilterArgs, clap::Error> {
+        use clap::Args;
+
+        let cmd = FilterArgs::augment_args(clap::Command::new("trashy"));
+        let matches =
+            cmd.try_get_matches_from(std::iter::once("trashy").chain(args.iter().copied()))?;
+        FilterArgs::from_arg_matches(&matches)
+    }
+
+    #[test]
+    fn arg_order() {
+        let args =
+            filter_args(&["--glob", "a", "--not", "--size", "+1", "--or", "b", "--glob", "c"]);
+        assert_eq!(
+            args.unwrap().order,
+            [("glob", 0), ("not", 0), ("size", 0), ("or", 0), ("patterns", 0), ("glob", 1)]
+        );
+        let args = filter_args(&["(", "--older", "1d", "--or=true", ")", "--not", "x"]);
+        assert_eq!(
+            args.unwrap().order,
+            [
+                ("patterns", 0),
+                ("before", 0),
+                ("or", 0),
+                ("patterns", 1),
+                ("not", 0),
+                ("patterns", 2)
+            ]
+        );
+        assert!(filter_args(&["--not=false", "x"]).is_err());
+        assert!(filter_args(&["--or=no", "x"]).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn owner_filter() {
+        let owner = |s| parse_owner_filter(s).map(|owner| (owner.uid, owner.gid));
+        assert_eq!(owner("root").unwrap(), (Some(0), None));
+        assert_eq!(owner("1000:").unwrap(), (Some(1000), None));
+        assert_eq!(owner(":50").unwrap(), (None, Some(50)));
+        assert_eq!(owner("root:0").unwrap(), (Some(0), Some(0)));
+        for s in ["", ":", "no-such-user-trashy", ":no-such-group-trashy", "root\0"] {
+            assert!(owner(s).is_err(), "Expected `{s}` to fail");
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn file_types() {
+        use std::os::unix::fs::symlink;
+
+        let dir = std::env::temp_dir().join(format!("trashy-file-types-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("file"), "x").unwrap();
+        symlink(dir.join("file"), dir.join("link")).unwrap();
+        symlink(dir.join("missing"), dir.join("broken")).unwrap();
+        let types = |name: &str| {
+            FileType::value_variants()
+                .iter()
+                .filter(|file_type| file_type.is_match(&dir.join(name)))
+                .copied()
+                .collect::<Vec<_>>()
+        };
+        let (file, link, broken) = (types("file"), types("link"), types("broken"));
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(file, [FileType::File]);
+        assert_eq!(link, [FileType::Symlink]);
+        assert_eq!(broken, [FileType::Symlink, FileType::BrokenSymlink]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn perm_filter() {
+        assert_eq!(parse_perm_filter("644").unwrap(), PermFilter::Exact(0o644));
+        assert_eq!(parse_perm_filter("-100").unwrap(), PermFilter::All(0o100));
+        assert_eq!(parse_perm_filter("/022").unwrap(), PermFilter::Any(0o22));
+        for s in ["", "-", "888", "+644", "10000", "u+x"] {
+            assert!(parse_perm_filter(s).is_err(), "Expected `{s}` to fail");
+        }
+        assert!(PermFilter::Exact(0o644).is_match_mode(0o644));
+        assert!(!PermFilter::Exact(0o644).is_match_mode(0o755));
+        assert!(PermFilter::All(0o600).is_match_mode(0o644));
+        assert!(!PermFilter::All(0o111).is_match_mode(0o744));
+        assert!(PermFilter::Any(0o022).is_match_mode(0o664));
+        assert!(!PermFilter::Any(0o022).is_match_mode(0o644));
+    }
+
+    fn item(path: &str) -> TrashItem {
+        let path = PathBuf::from(path);
+        TrashItem {
+            id: Default::default(),
+            name: path.file_name().unwrap().to_string_lossy().into_owned(),
+            original_parent: path.parent().unwrap().to_path_buf(),
+            time_deleted: 0,
+        }
+    }
+
+    #[test]
+    fn directories() {
+        // the directories don't exist, like directories that were removed after trashing
+        let is_match = |args: &[&str], path: &str| {
+            filter_args(args).unwrap().to_filters().unwrap().is_match(&item(path))
+        };
+        let dirs = ["--dir", "/trashy-missing/a", "--dir", "/trashy-missing/b/"];
+        let with = |extra: &[&'static str]| [&dirs[..], extra].concat();
+
+        assert!(is_match(&dirs, "/trashy-missing/a/x"));
+        assert!(is_match(&dirs, "/trashy-missing/b/c/d/y"));
+        assert!(!is_match(&dirs, "/trashy-missing/c/x"));
+        assert!(!is_match(&dirs, "/trashy-missing/ab/x"));
+        // the directory itself was trashed from its parent, but is still shown
+        assert!(is_match(&dirs, "/trashy-missing/a"));
+
+        assert!(is_match(&with(&["--max-depth", "2"]), "/trashy-missing/a/c/x"));
+        assert!(!is_match(&with(&["--max-depth", "2"]), "/trashy-missing/a/c/d/x"));
+        assert!(is_match(&with(&["--direct"]), "/trashy-missing/b/x"));
+        assert!(!is_match(&with(&["--direct"]), "/trashy-missing/b/c/x"));
+    }
+
+    #[test]
+    fn match_on() {
+        let is_match = |args: &[&str]| {
+            filter_args(args).unwrap().to_filters().unwrap().is_match(&item("/home/a/foo.txt"))
+        };
+        assert!(!is_match(&["--exact", "foo.txt"]));
+        assert!(is_match(&["--exact", "foo.txt", "--match-on", "name"]));
+        assert!(is_match(&["--exact", "/home/a/foo.txt", "--match-on", "path"]));
+        assert!(is_match(&["--exact", "/home/a", "--match-on", "parent"]));
+        assert!(!is_match(&["--substring", "foo", "--match-on", "parent"]));
+        assert_eq!(MatchOn::Parent.target(&item("/home/a/foo.txt")), "/home/a");
+    }
+
+    #[test]
+    fn size_filter() {
+        assert_eq!(parse_size_filter("+100M").unwrap(), SizeFilter::Min(100_000_000));
+        assert_eq!(parse_size_filter("-1k").unwrap(), SizeFilter::Max(1000));
+        assert_eq!(parse_size_filter("+2ki").unwrap(), SizeFilter::Min(2048));
+        assert_eq!(parse_size_filter("12").unwrap(), SizeFilter::Equals(12));
+        assert_eq!(parse_size_filter("3Gi").unwrap(), SizeFilter::Equals(3 * 1024u64.pow(3)));
+    }
+
+    #[test]
+    fn smart_case() {
+        let set =
+            |r#match, pattern| PatternSet::new(r#match, [pattern].iter(), Case::Smart, 0).unwrap();
+        assert!(set(Match::Regex, "readme").is_match("/home/README.md"));
+        assert!(!set(Match::Regex, "README").is_match("/home/readme.md"));
+        assert!(set(Match::Regex, r"\Wreadme").is_match("/home/README.md"));
+        assert!(set(Match::Substring, "readme").is_match("/home/README.md"));
+        assert!(set(Match::Glob, "**/readme.*").is_match("/home/README.md"));
+        assert!(set(Match::Exact, "readme.md").is_match("README.md"));
+        assert!(!set(Match::Exact, "Readme.md").is_match("README.md"));
+        assert!(set(Match::Fuzzy, "rdme").is_match("/home/README.md"));
+        assert!(!set(Match::Fuzzy, "Rdme").is_match("/home/readme.md"));
+    }
+
+    #[test]
+    fn fuzzy_score() {
+        let fuzzy = FuzzySet::new(["mnrs"].into_iter(), true, 0);
+        assert!(fuzzy.score("src/main.rs").is_some());
+        assert!(fuzzy.score("src/lib.rs").is_none());
+        assert!(fuzzy.score("src/main.rs") > fuzzy.score("src/mxxxnxxxrxxxs"));
+        let fuzzy = FuzzySet::new(["mnrs"].into_iter(), true, i64::MAX);
+        assert!(fuzzy.score("src/main.rs").is_none());
+    }
+
+    #[test]
+    fn size_filter_errors() {
+        assert!(parse_size_filter("").is_err());
+        assert!(parse_size_filter("+").is_err());
+        assert!(parse_size_filter("+1x").is_err());
+        assert!(parse_size_filter("k").is_err());
+        assert!(parse_size_filter("+99999999999999t").is_err());
+    }
 }
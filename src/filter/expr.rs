@@ -0,0 +1,185 @@
+use std::iter::Peekable;
+use std::path::Path;
+use std::vec;
+
+use anyhow::{bail, Result};
+
+use super::{FileType, Filter, Match};
+
+/// A filter argument or an operator, in the order given on the command line.
+pub enum Token<'a> {
+    Not,
+    Or,
+    Open,
+    Close,
+    Primary(Primary<'a>),
+}
+
+/// A single filter argument.
+pub enum Primary<'a> {
+    Before(&'a str),
+    Within(&'a str),
+    Deleted(&'a str),
+    /// `id` is the argument the pattern was given with.
+    /// Patterns of the same argument that are and-ed together form a single set.
+    Pattern {
+        id: &'static str,
+        r#match: Match,
+        pattern: &'a str,
+    },
+    Directory(&'a Path),
+    FileType(FileType),
+    Size(&'a str),
+    Extension(&'a str),
+    Owner(&'a str),
+    Permissions(&'a str),
+    ModifiedBefore(&'a str),
+    ModifiedAfter(&'a str),
+    Query(&'a str),
+}
+
+/// Parses `tokens` into filters that all must match.
+///
+/// '--not' binds tightest, then filters that are next to each other are and-ed, and finally
+/// '--or' combines those. `conjunction` turns primaries that are and-ed together into filters.
+pub fn parse<'a>(
+    tokens: Vec<Token<'a>>,
+    conjunction: &dyn Fn(Vec<Primary<'a>>) -> Result<Vec<Filter>>,
+) -> Result<Vec<Filter>> {
+    if tokens.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut parser = Parser { tokens: tokens.into_iter().peekable(), conjunction };
+    let filter = parser.parse_or()?;
+    if parser.tokens.next().is_some() {
+        bail!("Unexpected `)` without a matching `(`");
+    }
+    Ok(match filter {
+        Filter::And(filters) => filters,
+        filter => vec![filter],
+    })
+}
+
+struct Parser<'a, 'f> {
+    tokens: Peekable<vec::IntoIter<Token<'a>>>,
+    conjunction: &'f dyn Fn(Vec<Primary<'a>>) -> Result<Vec<Filter>>,
+}
+
+impl<'a, 'f> Parser<'a, 'f> {
+    fn parse_or(&mut self) -> Result<Filter> {
+        let mut alternatives = vec![self.parse_and()?];
+        while let Some(Token::Or) = self.tokens.peek() {
+            self.tokens.next();
+            alternatives.push(self.parse_and()?);
+        }
+        Ok(if alternatives.len() == 1 {
+            alternatives.pop().unwrap()
+        } else {
+            Filter::Or(alternatives)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<Filter> {
+        let mut primaries = Vec::new();
+        let mut filters = Vec::new();
+        loop {
+            match self.tokens.peek() {
+                None | Some(Token::Or) | Some(Token::Close) => break,
+                Some(Token::Primary(_)) => {
+                    if let Some(Token::Primary(primary)) = self.tokens.next() {
+                        primaries.push(primary);
+                    }
+                }
+                Some(Token::Not) | Some(Token::Open) => filters.push(self.parse_unary()?),
+            }
+        }
+        if primaries.is_empty() && filters.is_empty() {
+            match self.tokens.peek() {
+                Some(Token::Or) => bail!("Expected a filter before `--or`"),
+                Some(Token::Close) => bail!("Expected a filter before `)`"),
+                _ => bail!("Expected a filter after `--or` or `(`"),
+            }
+        }
+        filters.extend((self.conjunction)(primaries)?);
+        Ok(if filters.len() == 1 { filters.pop().unwrap() } else { Filter::And(filters) })
+    }
+
+    fn parse_unary(&mut self) -> Result<Filter> {
+        match self.tokens.next() {
+            Some(Token::Not) => Ok(Filter::Not(Box::new(self.parse_unary()?))),
+            Some(Token::Open) => {
+                let filter = self.parse_or()?;
+                match self.tokens.next() {
+                    Some(Token::Close) => Ok(filter),
+                    _ => bail!("Missing `)` to close `(`"),
+                }
+            }
+            Some(Token::Primary(primary)) => {
+                let mut filters = (self.conjunction)(vec![primary])?;
+                Ok(if filters.len() == 1 { filters.pop().unwrap() } else { Filter::And(filters) })
+            }
+            Some(Token::Or) | Some(Token::Close) | None => bail!("Expected a filter after `--not`"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filter::SizeFilter;
+
+    /// Parses space separated tokens, where numbers are filters that are rendered as themselves.
+    fn parse_str(s: &str) -> Result<String> {
+        let tokens = s
+            .split(' ')
+            .map(|token| match token {
+                "--not" => Token::Not,
+                "--or" => Token::Or,
+                "(" => Token::Open,
+                ")" => Token::Close,
+                size => Token::Primary(Primary::Size(size)),
+            })
+            .collect();
+        let conjunction = |primaries: Vec<Primary<'_>>| {
+            Ok(primaries
+                .into_iter()
+                .map(|primary| match primary {
+                    Primary::Size(s) => Filter::Size(SizeFilter::Equals(s.parse().unwrap())),
+                    _ => unreachable!(),
+                })
+                .collect())
+        };
+        let filters = parse(tokens, &conjunction)?;
+        Ok(filters.iter().map(render).collect::<Vec<_>>().join(" & "))
+    }
+
+    fn render(filter: &Filter) -> String {
+        let join = |filters: &[Filter], sep| {
+            format!("({})", filters.iter().map(render).collect::<Vec<_>>().join(sep))
+        };
+        match filter {
+            Filter::Size(SizeFilter::Equals(n)) => n.to_string(),
+            Filter::And(filters) => join(filters, " & "),
+            Filter::Or(filters) => join(filters, " | "),
+            Filter::Not(filter) => format!("!{}", render(filter)),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(parse_str("1 2 --or 3").unwrap(), "((1 & 2) | 3)");
+        assert_eq!(parse_str("1 --or 2 3 --or 4").unwrap(), "(1 | (2 & 3) | 4)");
+        assert_eq!(parse_str("( 1 --or ( 2 3 ) ) 4").unwrap(), "(1 | (2 & 3)) & 4");
+        assert_eq!(parse_str("--not --not 1").unwrap(), "!!1");
+        assert_eq!(parse_str("--not ( 1 --or 2 ) 3").unwrap(), "!(1 | 2) & 3");
+        assert_eq!(parse_str("--not 1 2").unwrap(), "!1 & 2");
+    }
+
+    #[test]
+    fn errors() {
+        for s in ["1 )", ") 1", "( 1", "( )", "1 --or", "--or 1", "1 --not", "1 --or --or 2"] {
+            assert!(parse_str(s).is_err(), "Expected `{s}` to fail");
+        }
+    }
+}
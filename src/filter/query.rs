@@ -0,0 +1,612 @@
+use std::fmt;
+use std::iter;
+use std::ops::Range;
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Error};
+use chrono::{DateTime, Duration, Utc};
+use clap::ValueEnum;
+
+use super::{
+    parse_owner_filter, parse_perm_filter, parse_size_filter, resolve_directory, Case, FileType,
+    Filter, Match, MatchOn, PatternSet, SizeFilter, TimeFilter,
+};
+use crate::time_syntax;
+
+/// Parses a query like `size > 10M and not path ~ "**/keep/**"` into a filter.
+///
+/// `and` binds tighter than `or`, and `not` binds tightest. Errors point into the query.
+pub fn parse(query: &str, now: DateTime<Utc>, case: Case) -> anyhow::Result<Filter> {
+    let tokens = lex(query).map_err(|e| e.into_error(query))?;
+    let mut parser = Parser { tokens, pos: 0, now, case };
+    parser
+        .parse_or()
+        .and_then(|filter| parser.expect_end().map(|()| filter))
+        .map_err(|e| e.into_error(query))
+}
+
+struct QueryError {
+    span: Range<usize>,
+    message: String,
+}
+
+impl QueryError {
+    fn new(span: Range<usize>, message: impl Into<String>) -> QueryError {
+        QueryError { span, message: message.into() }
+    }
+
+    /// Shows the query below the message, with carets under the part that caused the error.
+    fn into_error(self, query: &str) -> Error {
+        let offset = query[..self.span.start].chars().count();
+        let width = query[self.span].chars().count().max(1);
+        anyhow!("{}\n    {query}\n    {}{}", self.message, " ".repeat(offset), "^".repeat(width))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+    Glob,
+    NotGlob,
+    Regex,
+    In,
+    NotIn,
+}
+
+impl Op {
+    /// The operator with its sides swapped.
+    fn flip(self) -> Op {
+        match self {
+            Op::Lt => Op::Gt,
+            Op::Le => Op::Ge,
+            Op::Gt => Op::Lt,
+            Op::Ge => Op::Le,
+            op => op,
+        }
+    }
+}
+
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Op::Lt => "<",
+            Op::Le => "<=",
+            Op::Gt => ">",
+            Op::Ge => ">=",
+            Op::Eq => "=",
+            Op::Ne => "!=",
+            Op::Glob => "~",
+            Op::NotGlob => "!~",
+            Op::Regex => "=~",
+            Op::In => "in",
+            Op::NotIn => "not in",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Deleted,
+    Modified,
+    Size,
+    Type,
+    Ext,
+    Owner,
+    Perm,
+    Path,
+    Name,
+    Parent,
+    Dir,
+}
+
+impl Field {
+    const ALL: &'static [Field] = &[
+        Field::Deleted,
+        Field::Modified,
+        Field::Size,
+        Field::Type,
+        Field::Ext,
+        Field::Owner,
+        Field::Perm,
+        Field::Path,
+        Field::Name,
+        Field::Parent,
+        Field::Dir,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Field::Deleted => "deleted",
+            Field::Modified => "modified",
+            Field::Size => "size",
+            Field::Type => "type",
+            Field::Ext => "ext",
+            Field::Owner => "owner",
+            Field::Perm => "perm",
+            Field::Path => "path",
+            Field::Name => "name",
+            Field::Parent => "parent",
+            Field::Dir => "dir",
+        }
+    }
+
+    fn ops(self) -> &'static [Op] {
+        use Op::*;
+        match self {
+            Field::Deleted | Field::Modified | Field::Size => &[Lt, Le, Gt, Ge, Eq, Ne],
+            Field::Type | Field::Ext | Field::Owner | Field::Perm | Field::Dir => {
+                &[Eq, Ne, In, NotIn]
+            }
+            Field::Path | Field::Name | Field::Parent => &[Eq, Ne, Glob, NotGlob, Regex, In, NotIn],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TokenKind {
+    Word(String),
+    Quoted(String),
+    Op(Op),
+    Open,
+    Close,
+    Comma,
+    End,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    span: Range<usize>,
+}
+
+impl Token {
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(&self.kind, TokenKind::Word(word) if word.eq_ignore_ascii_case(keyword))
+    }
+}
+
+const SPECIAL_CHARS: &str = "(),\"'<>=!~";
+
+fn lex(query: &str) -> Result<Vec<Token>, QueryError> {
+    let mut tokens = Vec::new();
+    let mut chars = query.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        chars.next();
+        let kind = match c {
+            c if c.is_whitespace() => continue,
+            '(' => TokenKind::Open,
+            ')' => TokenKind::Close,
+            ',' => TokenKind::Comma,
+            '"' | '\'' => {
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, quote)) if quote == c => break,
+                        // only the quote and backslash are escaped so that regexes keep working
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, escaped)) if escaped == c || escaped == '\\' => {
+                                s.push(escaped)
+                            }
+                            Some((_, other)) => {
+                                s.push('\\');
+                                s.push(other);
+                            }
+                            None => {
+                                return Err(QueryError::new(
+                                    start..start + 1,
+                                    "Missing closing quote",
+                                ))
+                            }
+                        },
+                        Some((_, other)) => s.push(other),
+                        None => {
+                            return Err(QueryError::new(start..start + 1, "Missing closing quote"))
+                        }
+                    }
+                }
+                TokenKind::Quoted(s)
+            }
+            '<' | '>' | '=' | '!' | '~' => {
+                let (op, len) = match (c, chars.peek().map(|&(_, c)| c)) {
+                    ('<', Some('=')) => (Op::Le, 2),
+                    ('>', Some('=')) => (Op::Ge, 2),
+                    ('=', Some('=')) => (Op::Eq, 2),
+                    ('!', Some('=')) => (Op::Ne, 2),
+                    ('=', Some('~')) => (Op::Regex, 2),
+                    ('!', Some('~')) => (Op::NotGlob, 2),
+                    ('<', _) => (Op::Lt, 1),
+                    ('>', _) => (Op::Gt, 1),
+                    ('=', _) => (Op::Eq, 1),
+                    ('~', _) => (Op::Glob, 1),
+                    _ => {
+                        return Err(QueryError::new(
+                            start..start + 1,
+                            "Unexpected `!`, use `not` to negate a filter",
+                        ))
+                    }
+                };
+                if len == 2 {
+                    chars.next();
+                }
+                TokenKind::Op(op)
+            }
+            c => {
+                let mut s = String::from(c);
+                while let Some(&(_, c)) = chars.peek() {
+                    if c.is_whitespace() || SPECIAL_CHARS.contains(c) {
+                        break;
+                    }
+                    s.push(c);
+                    chars.next();
+                }
+                TokenKind::Word(s)
+            }
+        };
+        let end = chars.peek().map_or(query.len(), |&(i, _)| i);
+        tokens.push(Token { kind, span: start..end });
+    }
+    tokens.push(Token { kind: TokenKind::End, span: query.len()..query.len() });
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    now: DateTime<Utc>,
+    case: Case,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos]
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.pos].clone();
+        if token.kind != TokenKind::End {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn expect_end(&self) -> Result<(), QueryError> {
+        let token = self.peek();
+        match token.kind {
+            TokenKind::End => Ok(()),
+            TokenKind::Close => {
+                Err(QueryError::new(token.span.clone(), "Unexpected `)` without a matching `(`"))
+            }
+            _ => Err(QueryError::new(
+                token.span.clone(),
+                "Expected `and`, `or` or the end of the query",
+            )),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Filter, QueryError> {
+        let mut alternatives = vec![self.parse_and()?];
+        while self.peek().is_keyword("or") {
+            self.next();
+            alternatives.push(self.parse_and()?);
+        }
+        Ok(if alternatives.len() == 1 {
+            alternatives.pop().unwrap()
+        } else {
+            Filter::Or(alternatives)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<Filter, QueryError> {
+        let mut filters = vec![self.parse_unary()?];
+        while self.peek().is_keyword("and") {
+            self.next();
+            filters.push(self.parse_unary()?);
+        }
+        Ok(if filters.len() == 1 { filters.pop().unwrap() } else { Filter::And(filters) })
+    }
+
+    fn parse_unary(&mut self) -> Result<Filter, QueryError> {
+        if self.peek().is_keyword("not") {
+            self.next();
+            return Ok(Filter::Not(Box::new(self.parse_unary()?)));
+        }
+        if self.peek().kind != TokenKind::Open {
+            return self.parse_comparison();
+        }
+        let open = self.next();
+        let filter = self.parse_or()?;
+        let token = self.next();
+        match token.kind {
+            TokenKind::Close => Ok(filter),
+            TokenKind::End => Err(QueryError::new(open.span, "Missing `)` to close `(`")),
+            _ => Err(QueryError::new(token.span, "Expected `and`, `or` or `)`")),
+        }
+    }
+
+    fn parse_comparison(&mut self) -> Result<Filter, QueryError> {
+        let token = self.next();
+        let field = match &token.kind {
+            TokenKind::Word(word) if ["and", "or", "in"].iter().any(|kw| token.is_keyword(kw)) => {
+                return Err(QueryError::new(
+                    token.span,
+                    format!("Expected a filter before `{word}`"),
+                ));
+            }
+            TokenKind::Word(word) => {
+                match Field::ALL.iter().find(|field| field.name().eq_ignore_ascii_case(word)) {
+                    Some(&field) => field,
+                    None => {
+                        let fields: Vec<_> =
+                            Field::ALL.iter().map(|field| format!("`{}`", field.name())).collect();
+                        return Err(QueryError::new(
+                            token.span,
+                            format!(
+                                "Unknown field `{word}`, expected one of {}",
+                                fields.join(", ")
+                            ),
+                        ));
+                    }
+                }
+            }
+            _ => {
+                return Err(QueryError::new(
+                    token.span,
+                    "Expected a filter like `size > 10M` or `name = foo.txt`",
+                ))
+            }
+        };
+
+        let op_token = self.next();
+        let op = match op_token.kind {
+            TokenKind::Op(op) => Some(op),
+            _ if op_token.is_keyword("in") => Some(Op::In),
+            _ if op_token.is_keyword("not") && self.peek().is_keyword("in") => {
+                self.next();
+                Some(Op::NotIn)
+            }
+            _ => None,
+        };
+        let op_span = op_token.span.start..self.tokens[self.pos - 1].span.end;
+        let op = match op {
+            Some(op) if field.ops().contains(&op) => op,
+            _ => {
+                let ops: Vec<_> = field.ops().iter().map(|op| format!("`{op}`")).collect();
+                let message = match op {
+                    Some(op) => format!("`{op}` can't be used with `{}`", field.name()),
+                    None => format!("Expected an operator after `{}`", field.name()),
+                };
+                return Err(QueryError::new(
+                    op_span,
+                    format!("{message}, expected one of {}", ops.join(", ")),
+                ));
+            }
+        };
+
+        match op {
+            Op::In | Op::NotIn => {
+                let mut alternatives = self
+                    .parse_list()?
+                    .into_iter()
+                    .map(|(value, span)| {
+                        self.compile(field, Op::Eq, &value)
+                            .map_err(|e| QueryError::new(span, format!("{e:#}")))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                let filter = if alternatives.len() == 1 {
+                    alternatives.pop().unwrap()
+                } else {
+                    Filter::Or(alternatives)
+                };
+                Ok(if op == Op::NotIn { Filter::Not(Box::new(filter)) } else { filter })
+            }
+            op => {
+                let (value, span) = self.parse_value()?;
+                self.compile(field, op, &value).map_err(|e| QueryError::new(span, format!("{e:#}")))
+            }
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<(String, Range<usize>), QueryError> {
+        let token = self.next();
+        match token.kind {
+            TokenKind::Word(value) | TokenKind::Quoted(value) => Ok((value, token.span)),
+            _ => Err(QueryError::new(
+                token.span,
+                format!(
+                    "Expected a value, quote values containing spaces or any of {SPECIAL_CHARS}"
+                ),
+            )),
+        }
+    }
+
+    fn parse_list(&mut self) -> Result<Vec<(String, Range<usize>)>, QueryError> {
+        let token = self.next();
+        if token.kind != TokenKind::Open {
+            return Err(QueryError::new(token.span, "Expected a list of values like `(a, b)`"));
+        }
+        let mut values = vec![self.parse_value()?];
+        loop {
+            let token = self.next();
+            match token.kind {
+                TokenKind::Comma => values.push(self.parse_value()?),
+                TokenKind::Close => return Ok(values),
+                _ => return Err(QueryError::new(token.span, "Expected `,` or `)`")),
+            }
+        }
+    }
+
+    fn compile(&self, field: Field, op: Op, value: &str) -> anyhow::Result<Filter> {
+        match op {
+            Op::Ne => return Ok(Filter::Not(Box::new(self.compile(field, Op::Eq, value)?))),
+            Op::NotGlob => {
+                return Ok(Filter::Not(Box::new(self.compile(field, Op::Glob, value)?)))
+            }
+            _ => (),
+        }
+        Ok(match field {
+            Field::Deleted => self.compile_time(op, value, Filter::Time)?,
+            Field::Modified => self.compile_time(op, value, Filter::Modified)?,
+            Field::Size => compile_size(op, value)?,
+            Field::Type => {
+                Filter::FileTypes(vec![FileType::from_str(value, true).map_err(|_| {
+                    let types: Vec<_> = FileType::value_variants()
+                        .iter()
+                        .filter_map(|file_type| file_type.to_possible_value())
+                        .map(|value| format!("`{}`", value.get_name()))
+                        .collect();
+                    anyhow!("Invalid type `{value}`, expected one of {}", types.join(", "))
+                })?])
+            }
+            Field::Ext => Filter::Extensions(vec![value.trim_start_matches('.').to_lowercase()]),
+            Field::Owner => Filter::Owner(parse_owner_filter(value)?),
+            Field::Perm => Filter::Permissions(parse_perm_filter(value)?),
+            Field::Dir => Filter::Directories(vec![resolve_directory(Path::new(value))?], None),
+            Field::Path | Field::Name | Field::Parent => {
+                let match_on = match field {
+                    Field::Path => MatchOn::Path,
+                    Field::Name => MatchOn::Name,
+                    _ => MatchOn::Parent,
+                };
+                let r#match = match op {
+                    Op::Glob => Match::Glob,
+                    Op::Regex => Match::Regex,
+                    _ => Match::Exact,
+                };
+                Filter::PatternSet(
+                    PatternSet::new(r#match, iter::once(value), self.case, 0)?,
+                    match_on,
+                )
+            }
+        })
+    }
+
+    /// Compiles a comparison of a time.
+    ///
+    /// Durations are compared as ages, so `deleted < 2d` means trashed less than two days ago.
+    /// `=` takes a day or a range like `--deleted`.
+    fn compile_time(
+        &self,
+        op: Op,
+        value: &str,
+        wrap: fn(TimeFilter) -> Filter,
+    ) -> anyhow::Result<Filter> {
+        if op == Op::Eq {
+            let (start, end) = time_syntax::parse_time_span(self.now, value)?;
+            return Ok(wrap(TimeFilter::Between(start, end)));
+        }
+        let op = if humantime::parse_duration(value).is_ok() { op.flip() } else { op };
+        let time = time_syntax::parse_time(self.now, value)?;
+        Ok(wrap(match op {
+            Op::Lt => TimeFilter::Before(time),
+            Op::Le => TimeFilter::Between(None, Some(time + Duration::nanoseconds(1))),
+            Op::Gt => TimeFilter::After(time),
+            Op::Ge => TimeFilter::Between(Some(time), None),
+            _ => unreachable!("BUG: `{op}` is not a time comparison"),
+        }))
+    }
+}
+
+fn compile_size(op: Op, value: &str) -> anyhow::Result<Filter> {
+    let size = match parse_size_filter(value)? {
+        SizeFilter::Equals(size) => size,
+        _ => bail!("Invalid size `{value}`, use an operator like `>` instead of `+` or `-`"),
+    };
+    // an empty `or` never matches, which is what `size < 0` should do
+    let never = || Filter::Or(Vec::new());
+    Ok(match op {
+        Op::Lt => {
+            size.checked_sub(1).map_or_else(never, |size| Filter::Size(SizeFilter::Max(size)))
+        }
+        Op::Le => Filter::Size(SizeFilter::Max(size)),
+        Op::Gt => {
+            size.checked_add(1).map_or_else(never, |size| Filter::Size(SizeFilter::Min(size)))
+        }
+        Op::Ge => Filter::Size(SizeFilter::Min(size)),
+        Op::Eq => Filter::Size(SizeFilter::Equals(size)),
+        _ => unreachable!("BUG: `{op}` is not a size comparison"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use chrono::TimeZone;
+    use trash::TrashItem;
+
+    use super::*;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 17, 12, 0, 0).unwrap()
+    }
+
+    fn item(path: &str, deleted: DateTime<Utc>) -> TrashItem {
+        let path = PathBuf::from(path);
+        TrashItem {
+            id: Default::default(),
+            name: path.file_name().unwrap().to_string_lossy().into_owned(),
+            original_parent: path.parent().unwrap().to_path_buf(),
+            time_deleted: deleted.timestamp(),
+        }
+    }
+
+    #[test]
+    fn matches() {
+        let is_match = |query: &str, item: &TrashItem| {
+            parse(query, now(), Case::Smart).unwrap().is_match(item)
+        };
+        let old_log = item("/home/a/old.log", now() - Duration::days(3));
+        let new_tmp = item("/home/a/keep/new.TMP", now() - Duration::hours(1));
+        let new_txt = item("/home/b/new.txt", now() - Duration::hours(1));
+        let new_log = item("/home/a/new.log", now() - Duration::hours(1));
+
+        let query = r#"ext in (log, tmp) and deleted < 2d and not path ~ "**/keep/**""#;
+        assert!(is_match(query, &new_log));
+        assert!(!is_match(query, &old_log));
+        assert!(!is_match(query, &new_tmp));
+        assert!(!is_match(query, &new_txt));
+        let query = "(ext = log or name =~ '^new') AND parent != /home/b";
+        assert!(is_match(query, &old_log));
+        assert!(is_match(query, &new_tmp));
+        assert!(!is_match(query, &new_txt));
+        assert!(is_match("deleted > 2d", &old_log));
+        assert!(!is_match("deleted > 2d", &new_txt));
+        assert!(is_match("deleted < 2024-01-17", &old_log));
+        assert!(is_match("deleted = today", &new_txt));
+        assert!(is_match("name not in (old.log, 'new.txt')", &new_tmp));
+        assert!(is_match("dir = /home/a", &new_tmp));
+    }
+
+    #[test]
+    fn errors() {
+        let error = |query: &str| format!("{:#}", parse(query, now(), Case::Smart).unwrap_err());
+        assert_eq!(
+            error("size >"),
+            "Expected a value, quote values containing spaces or any of (),\"'<>=!~\n    \
+             size >\n          ^"
+        );
+        assert_eq!(
+            error("size ~ 10M"),
+            "`~` can't be used with `size`, expected one of `<`, `<=`, `>`, `>=`, `=`, `!=`\n    \
+             size ~ 10M\n         ^"
+        );
+        assert_eq!(error("(ext = log"), "Missing `)` to close `(`\n    (ext = log\n    ^");
+        assert_eq!(
+            error("size > 10X"),
+            "Invalid size unit `X` in `10X`\n    size > 10X\n           ^^^"
+        );
+        assert!(error("colour = red").starts_with("Unknown field `colour`"));
+        assert!(error("name = 'foo").starts_with("Missing closing quote"));
+        assert!(error("name = 'foo\\").starts_with("Missing closing quote"));
+        assert!(error(r#"name = "foo\""#).starts_with("Missing closing quote"));
+        assert!(error("ext = log ext = tmp").starts_with("Expected `and`, `or` or the end"));
+        assert!(error("ext = log)").starts_with("Unexpected `)`"));
+        assert!(error("and ext = log").starts_with("Expected a filter before `and`"));
+        assert!(error("!ext = log").starts_with("Unexpected `!`"));
+    }
+}
@@ -0,0 +1,101 @@
+//! Query and act on the trash with the same filtering and range semantics as the `trashy` command.
+//!
+//! Filters are built from [`FilterOptions`], which holds the same options as the command line,
+//! and are then used to list trash items, optionally narrowed down further by ranges:
+//!
+//! ```no_run
+//! use trashy::{FilterArgs, FilterOptions, SortBy};
+//!
+//! let mut options = FilterOptions::default();
+//! options.glob.push("**/*.log".to_string());
+//! options.before.push("1week".to_string());
+//! let filters = FilterArgs::new(options).to_filters()?;
+//! let items = trashy::list(SortBy::Time, false, None, filters)?;
+//! let ranges = trashy::range_syntax::parse_range_set("0..3 ^1", items.len() as u32)?;
+//! for (i, item) in trashy::filter_by_ranges(&items, ranges)? {
+//!     println!("{i} {}", item.original_path().display());
+//! }
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+//!
+//! The items are [`trash::TrashItem`]s, so they can be restored or removed with the `trash` crate.
+//! Items that were listed elsewhere can be filtered and sorted the same way with [`process_items`]:
+//!
+//! ```
+//! use trash::TrashItem;
+//! use trashy::{FilterArgs, FilterOptions, SortBy};
+//!
+//! let item = |name: &str, time_deleted| TrashItem {
+//!     id: name.into(),
+//!     name: name.to_string(),
+//!     original_parent: "/home/user".into(),
+//!     time_deleted,
+//! };
+//! let items = vec![item("b.log", 1), item("c.txt", 2), item("a.log", 3), item("d.log", 4)];
+//!
+//! let mut options = FilterOptions::default();
+//! options.ext.push("log".to_string());
+//! let filters = FilterArgs::new(options).to_filters()?;
+//! let items = trashy::process_items(SortBy::Path, false, None, filters, items);
+//! let names: Vec<_> = items.iter().map(|item| item.name.as_str()).collect();
+//! assert_eq!(names, ["a.log", "b.log", "d.log"]);
+//!
+//! let ranges = trashy::range_syntax::parse_range_set("1..", items.len() as u32)?;
+//! let picked: Vec<_> = trashy::filter_by_ranges(&items, ranges)?
+//!     .into_iter()
+//!     .map(|(i, item)| (i, item.name))
+//!     .collect();
+//! assert_eq!(picked, [(1, "b.log".to_string()), (2, "d.log".to_string())]);
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+pub mod filter;
+pub mod range_set;
+pub mod range_syntax;
+pub mod time_syntax;
+
+mod app;
+mod config;
+mod exitcode;
+mod print;
+mod range;
+mod trash_item;
+mod utils;
+
+pub use app::command::list::{
+    display_indexed_items, display_item, display_item_date, display_items, filter_by_ranges,
+    indexed_items_to_table, list, list_only, process_items, SortBy,
+};
+pub use app::{Column, ConfigArgs, Status, TimeDisplayMode};
+pub use filter::{Filter, FilterArgs, FilterOptions, Filters};
+pub use range_set::RangeSet;
+
+use std::env;
+use std::ffi::OsString;
+
+use anyhow::Result;
+use clap::{CommandFactory, FromArgMatches};
+
+use app::Args;
+use config::Config;
+use exitcode::{ExitCode, PromptError};
+
+/// Runs the `trashy` command with the arguments of the process, then exits with its exit code.
+pub fn run_cli() -> ! {
+    match try_run_cli() {
+        Ok(()) => ExitCode::Success.exit(),
+        Err(e) => match e.downcast_ref::<PromptError>() {
+            Some(prompt_error) => prompt_error.exit_code().exit_with_msg(prompt_error),
+            None => ExitCode::Error.exit_with_msg(format!("{e:#}")),
+        },
+    }
+}
+
+fn try_run_cli() -> Result<()> {
+    let args: Vec<OsString> = env::args_os().collect();
+    let config = Config::from_args(&args)?;
+    let cmd = Args::command();
+    let args = config.expand_alias(&cmd, args)?;
+    let matches = config.apply(cmd).get_matches_from(args);
+    Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit()).run(&config)
+}
@@ -1,7 +1,31 @@
-use std::fmt;
+use std::{env, fmt};
 
 use ansi_term::Colour::Red;
 
 pub fn err_display(s: impl fmt::Display) {
-    eprintln!("{} {s}", Red.bold().paint("error:"));
+    if use_color() {
+        eprintln!("{} {s}", Red.bold().paint("error:"));
+    } else {
+        eprintln!("error: {s}");
+    }
+}
+
+/// Whether to color messages on stderr, which happens before '--color' can be known.
+fn use_color() -> bool {
+    match color_from_env() {
+        Some((use_color, _)) => use_color,
+        None => atty::is(atty::Stream::Stderr),
+    }
+}
+
+/// Whether to use color according to the `NO_COLOR` and `CLICOLOR_FORCE` conventions,
+/// together with the variable that decided it. `NO_COLOR` wins if both are set.
+pub fn color_from_env() -> Option<(bool, &'static str)> {
+    if env::var_os("NO_COLOR").map_or(false, |v| !v.is_empty()) {
+        Some((false, "NO_COLOR"))
+    } else if env::var_os("CLICOLOR_FORCE").map_or(false, |v| !v.is_empty() && v != "0") {
+        Some((true, "CLICOLOR_FORCE"))
+    } else {
+        None
+    }
 }
@@ -1,5 +1,6 @@
 use std::{cmp, ops};
 
+/// The indices from `start` up to but not including `end`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Range {
     start: u32,
@@ -39,12 +40,23 @@ impl Range {
     pub fn to_std(self) -> ops::Range<usize> {
         self.start as usize..self.end as usize
     }
+}
+
+impl IntoIterator for Range {
+    type Item = u32;
+    type IntoIter = ops::Range<u32>;
 
-    pub fn into_iter(self) -> impl Iterator<Item = u32> {
+    fn into_iter(self) -> Self::IntoIter {
         self.start..self.end
     }
 }
 
+impl From<Range> for ops::Range<u32> {
+    fn from(range: Range) -> Self {
+        range.start..range.end
+    }
+}
+
 impl From<ops::Range<u32>> for Range {
     fn from(range: ops::Range<u32>) -> Self {
         Range { start: range.start, end: range.end }
@@ -1,14 +1,65 @@
+//! Sets of item indices.
+
+use std::{cmp, ops};
+
 use crate::range::Range;
 
+/// Sorted ranges that neither overlap nor touch each other.
 #[derive(Debug, PartialEq, Eq)]
 pub struct RangeSet {
     ranges: Vec<Range>,
 }
 
 impl RangeSet {
+    /// Whether the set contains no indices.
     pub fn is_empty(&self) -> bool {
         self.ranges.is_empty()
     }
+
+    /// The indices that are in both sets.
+    pub fn intersection(&self, other: &RangeSet) -> RangeSet {
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a, b) = (self.ranges[i], other.ranges[j]);
+            let start = cmp::max(a.start(), b.start());
+            let end = cmp::min(a.end(), b.end());
+            if start < end {
+                ranges.push(Range::new(start, end));
+            }
+            if a.end() < b.end() {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        RangeSet { ranges }
+    }
+
+    /// The indices that are in this set but not in `other`.
+    pub fn difference(&self, other: &RangeSet) -> RangeSet {
+        let len = self.ranges.last().map_or(0, |range| range.end());
+        self.intersection(&other.complement(len))
+    }
+
+    /// The indices below `len` that are not in this set.
+    pub fn complement(&self, len: u32) -> RangeSet {
+        let mut ranges = Vec::new();
+        let mut start = 0;
+        for range in &self.ranges {
+            if range.start() >= len {
+                break;
+            }
+            if start < range.start() {
+                ranges.push(Range::new(start, range.start()));
+            }
+            start = range.end();
+        }
+        if start < len {
+            ranges.push(Range::new(start, len));
+        }
+        RangeSet { ranges }
+    }
 }
 
 impl From<Vec<Range>> for RangeSet {
@@ -19,45 +70,55 @@ impl From<Vec<Range>> for RangeSet {
     }
 }
 
-impl From<RangeSet> for Vec<Range> {
-    fn from(range_set: RangeSet) -> Self {
-        range_set.ranges
-    }
-}
-
-impl FromIterator<Range> for RangeSet {
-    fn from_iter<T: IntoIterator<Item = Range>>(iter: T) -> Self {
-        RangeSet::from(iter.into_iter().collect::<Vec<_>>())
+impl FromIterator<ops::Range<u32>> for RangeSet {
+    /// Collects the indices of all ranges, where ranges that start at or after their end are empty.
+    fn from_iter<T: IntoIterator<Item = ops::Range<u32>>>(iter: T) -> Self {
+        RangeSet::from(iter.into_iter().map(Range::from).collect::<Vec<_>>())
     }
 }
 
 impl IntoIterator for RangeSet {
-    type Item = Range;
-    type IntoIter = <Vec<Range> as IntoIterator>::IntoIter;
+    type Item = ops::Range<u32>;
+    type IntoIter = std::iter::Map<std::vec::IntoIter<Range>, fn(Range) -> ops::Range<u32>>;
 
+    /// The ranges in ascending order.
     fn into_iter(self) -> Self::IntoIter {
-        self.ranges.into_iter()
+        self.ranges.into_iter().map(ops::Range::from)
     }
 }
 
+/// Merges ranges sorted by their start that overlap or touch, and removes empty ranges.
 fn normalize(ranges: &mut Vec<Range>) {
-    if ranges.len() < 2 {
-        return;
-    }
-    let mut i = ranges.len() - 1;
-    let mut d = 0;
-    loop {
-        match ranges[i - 1].union(ranges[i]) {
-            None => (),
-            Some(range) => {
-                ranges[i - 1] = range;
-                d += 1;
-            }
+    ranges.retain(|range| range.start() < range.end());
+    let mut merged: Vec<Range> = Vec::with_capacity(ranges.len());
+    for &range in ranges.iter() {
+        match merged.last().and_then(|last| last.union(range)) {
+            Some(union) => *merged.last_mut().unwrap() = union,
+            None => merged.push(range),
         }
-        if i == 1 {
-            break;
-        }
-        i -= 1;
     }
-    ranges.drain((ranges.len() - d)..);
+    *ranges = merged;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set<const N: usize>(ranges: [std::ops::Range<u32>; N]) -> RangeSet {
+        ranges.into_iter().collect()
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn operations() {
+        assert_eq!(set([1..3, 2..4, 10..11, 4..5]), set([1..5, 10..11]));
+        assert_eq!(
+            set([0..5, 8..12]).intersection(&set([3..9, 11..20])),
+            set([3..5, 8..9, 11..12])
+        );
+        assert_eq!(set([0..10]).difference(&set([2..3, 5..7])), set([0..2, 3..5, 7..10]));
+        assert_eq!(set([2..3, 5..7]).complement(8), set([0..2, 3..5, 7..8]));
+        assert_eq!(set([2..3, 5..9]).complement(6), set([0..2, 3..5]));
+        assert!(set([0..4]).complement(4).is_empty());
+    }
 }
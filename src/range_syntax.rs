@@ -1,77 +1,140 @@
-use anyhow::{bail, Context, Result};
+//! The syntax of '--ranges', like `1 5..9 ^7`.
 
-use crate::{range::Range, range_set};
+use std::ops;
 
-pub fn parse_range_set(s: &str) -> Result<range_set::RangeSet> {
-    parse_ranges(s).collect()
-}
+use anyhow::{bail, Context, Result};
+
+use crate::range_set::RangeSet;
 
-pub fn parse_ranges(s: &str) -> impl Iterator<Item = Result<Range>> + '_ {
-    s.split(char::is_whitespace).map(parse_range)
+/// Parses ranges separated by whitespace or commas into a set of indices.
+///
+/// `len` is the number of items the indices refer to. It is used by negative indices, which
+/// count from the end, and by ranges without an end. Ranges starting with `^` are excluded,
+/// and if there are only exclusions, they are excluded from all items.
+pub fn parse_range_set(s: &str, len: u32) -> Result<RangeSet> {
+    let mut include = Vec::new();
+    let mut exclude = Vec::new();
+    for term in s.split(|c: char| c.is_whitespace() || c == ',').filter(|term| !term.is_empty()) {
+        match term.strip_prefix('^') {
+            Some(term) => exclude.push(parse_range(term, len)?),
+            None => include.push(parse_range(term, len)?),
+        }
+    }
+    if include.is_empty() && exclude.is_empty() {
+        bail!("Could not parse empty string");
+    }
+    let exclude: RangeSet = exclude.into_iter().collect();
+    Ok(if include.is_empty() {
+        exclude.complement(len)
+    } else {
+        include.into_iter().collect::<RangeSet>().difference(&exclude)
+    })
 }
 
-pub fn parse_range(s: &str) -> Result<Range> {
+/// Parses `N`, `A..B`, `A..=B`, `A..` or `..B`, where each index may be negative.
+pub fn parse_range(s: &str, len: u32) -> Result<ops::Range<u32>> {
     if s.is_empty() {
         bail!("Could not parse empty string");
     }
-    let mut split = s.split("..");
-    let start = split.next().expect("BUG: must have at least one");
-    let start = start
-        .parse::<u32>()
-        .with_context(|| format!("Failed to parse `{}` before - into a number", start))?;
-    match split.next() {
-        Some(end) => {
-            let end = end
+    let (start, end) = match s.split_once("..") {
+        Some(range) => range,
+        None => {
+            let index = parse_index(s, len)?;
+            let end = index.checked_add(1).with_context(|| format!("Index `{s}` is too large"))?;
+            return Ok(index..end);
+        }
+    };
+    if end.contains("..") {
+        bail!("Unexpected second `..` in `{s}`");
+    }
+    let start = if start.is_empty() { 0 } else { parse_index(start, len)? };
+    let (end, inclusive) = match end.strip_prefix('=') {
+        Some("") => bail!("Expected an end after `..=` in `{s}`"),
+        Some(end) => (parse_index(end, len)?, true),
+        None if end.is_empty() => (len, false),
+        None => (parse_index(end, len)?, false),
+    };
+    // an empty range would silently select nothing instead of the items that were meant
+    if start > end {
+        bail!("Range `{s}` starts after its end");
+    }
+    let end = if inclusive {
+        end.checked_add(1).with_context(|| format!("Index `{end}` is too large"))?
+    } else {
+        end
+    };
+    Ok(start..end)
+}
+
+/// Parses an index, where negative indices count from `len`.
+fn parse_index(s: &str, len: u32) -> Result<u32> {
+    match s.strip_prefix('-') {
+        Some(from_end) => {
+            let from_end = from_end
                 .parse::<u32>()
-                .with_context(|| format!("Failed to parse `{}` after - into a number", end))?;
-            if split.next().is_some() {
-                bail!("Unexpected second - found");
+                .with_context(|| format!("Failed to parse `{s}` into a number"))?;
+            if from_end == 0 || from_end > len {
+                bail!("Index `{s}` is out of bounds, there are {len} items");
             }
-            Ok(Range::new(start, end))
+            Ok(len - from_end)
         }
-        None => Ok(Range::new(start, start + 1)),
+        None => s.parse::<u32>().with_context(|| format!("Failed to parse `{s}` into a number")),
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::ops;
-
-    use crate::range_set::RangeSet;
-
     use super::*;
 
-    pub fn parse_range_set(s: &str) -> Result<RangeSet> {
-        parse_ranges(s).collect()
-    }
+    const LEN: u32 = 20;
 
     fn parse_succeed<const N: usize>(s: &str, expect: [ops::Range<u32>; N]) {
         assert_eq!(
-            parse_range_set(s)
+            parse_range_set(s, LEN)
                 .unwrap_or_else(|_| panic!("Failed to parse str `{}` into a restore index", s)),
-            expect.into_iter().map(Into::into).collect()
+            expect.into_iter().collect()
         );
     }
 
     fn parse_fail(s: &str) {
-        let res = parse_range_set(s);
+        let res = parse_range_set(s, LEN);
         assert!(res.is_err(), "Expected parsing to fail, got {:?}", res.unwrap());
     }
 
     #[test]
+    #[allow(clippy::single_range_in_vec_init)]
     fn test_succeed() {
         parse_succeed("123..1234", [123..1234]);
         parse_succeed("7 8 4", [4..5, 7..8, 8..9]);
         parse_succeed("5", [5..6]);
+        parse_succeed("1..=3", [1..4]);
+        parse_succeed("15..", [15..20]);
+        parse_succeed("..5", [0..5]);
+        parse_succeed("..=5", [0..6]);
+        parse_succeed("-1", [19..20]);
+        parse_succeed("-3..-1", [17..19]);
+        parse_succeed("1,3, 5..7", [1..2, 3..4, 5..7]);
+        parse_succeed("..10 ^3 ^5..=6", [0..3, 4..5, 7..10]);
+        parse_succeed("^1..", [0..1]);
+        parse_succeed("7  8", [7..9]);
     }
 
     #[test]
     fn parse_errors() {
-        parse_fail("2340958..");
-        parse_fail("2340958..    ");
         parse_fail("2340958.    ");
-        parse_fail("..1234");
         parse_fail(".1234");
         parse_fail("");
+        parse_fail(" , ");
+        parse_fail("1..2..3");
+        parse_fail("1..=");
+        parse_fail("-0");
+        parse_fail("-21");
+        parse_fail("^");
+        parse_fail("a..b");
+        parse_fail("5..=2");
+        parse_fail("3..=1");
+        parse_fail("5..2");
+        parse_fail("-1..-3");
+        parse_fail("25..");
     }
 }
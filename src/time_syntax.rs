@@ -0,0 +1,167 @@
+//! The syntax of times and time spans in filters, like `2d`, `yesterday` or `2024-01-17 12:00`.
+
+use anyhow::{anyhow, bail, Result};
+use chrono::{
+    DateTime, Datelike, Duration, Local, LocalResult, NaiveDate, NaiveDateTime, TimeZone, Utc,
+    Weekday,
+};
+
+/// A start and an end in time, where missing ends are unbounded.
+pub type TimeSpan = (Option<DateTime<Utc>>, Option<DateTime<Utc>>);
+
+const DATETIME_FORMATS: &[&str] = &["%F %T", "%F %R", "%FT%T", "%FT%R"];
+
+/// Parses a point in time, relative to `now`.
+///
+/// Accepts durations like `2d` (meaning that long before `now`), `now`, days like `today`,
+/// `yesterday` and `last monday` (meaning the start of that day), RFC 3339 and
+/// `YYYY-MM-DD` with an optional `HH:MM` or `HH:MM:SS` time.
+pub fn parse_time(now: DateTime<Utc>, s: &str) -> Result<DateTime<Utc>> {
+    let s = s.trim();
+    if s == "now" {
+        return Ok(now);
+    }
+    if let Ok(duration) = humantime::parse_duration(s) {
+        let duration =
+            Duration::from_std(duration).map_err(|_| anyhow!("Duration `{s}` is too long"))?;
+        return now
+            .checked_sub_signed(duration)
+            .ok_or_else(|| anyhow!("Duration `{s}` is too long"));
+    }
+    if let Some(day) = parse_day(now, s) {
+        return start_of_day(day);
+    }
+    parse_absolute_time(s)
+}
+
+/// Parses a point in time that does not depend on the current time.
+pub fn parse_absolute_time(s: &str) -> Result<DateTime<Utc>> {
+    let s = s.trim();
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(s) {
+        return Ok(datetime.into());
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(s, "%F") {
+        return start_of_day(date);
+    }
+    DATETIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(s, format).ok())
+        .and_then(|datetime| Local.from_local_datetime(&datetime).earliest())
+        .map(Into::into)
+        .ok_or_else(|| anyhow!("Invalid duration or date `{s}`"))
+}
+
+/// Parses a span of time.
+///
+/// Either a range of two points in time separated by `..`, where both ends are optional and
+/// the end is exclusive, or a single day like `yesterday` or `2024-01-01`.
+pub fn parse_time_span(now: DateTime<Utc>, s: &str) -> Result<TimeSpan> {
+    let s = s.trim();
+    if let Some((start, end)) = s.split_once("..") {
+        let parse_end = |s: &str| match s.trim() {
+            "" => Ok(None),
+            s => parse_time(now, s).map(Some),
+        };
+        let (start, end) = (parse_end(start)?, parse_end(end)?);
+        if let (Some(start), Some(end)) = (start, end) {
+            if start > end {
+                bail!("Time span `{s}` starts after its end");
+            }
+        }
+        return Ok((start, end));
+    }
+    match parse_day(now, s).or_else(|| NaiveDate::parse_from_str(s, "%F").ok()) {
+        Some(day) => Ok((Some(start_of_day(day)?), Some(start_of_day(day + Duration::days(1))?))),
+        None => bail!(
+            "Invalid time span `{s}`, expected a day or a range like `2024-01-01..2024-02-01`"
+        ),
+    }
+}
+
+/// Parses a day relative to `now`, like `today`, `yesterday`, `monday` or `last monday`.
+fn parse_day(now: DateTime<Utc>, s: &str) -> Option<NaiveDate> {
+    let today = now.with_timezone(&Local).date_naive();
+    let s = s.to_lowercase();
+    match s.as_str() {
+        "today" => Some(today),
+        "yesterday" => today.pred_opt(),
+        s => {
+            let weekday = s.strip_prefix("last ").unwrap_or(s).trim().parse::<Weekday>().ok()?;
+            let days_back =
+                (today.weekday().num_days_from_monday() + 7 - weekday.num_days_from_monday()) % 7;
+            // the weekday is always in the past, so 'last monday' on a monday is a week ago
+            let days_back = if days_back == 0 { 7 } else { days_back };
+            Some(today - Duration::days(days_back.into()))
+        }
+    }
+}
+
+fn start_of_day(date: NaiveDate) -> Result<DateTime<Utc>> {
+    let midnight = date.and_hms_opt(0, 0, 0).unwrap();
+    match Local.from_local_datetime(&midnight) {
+        LocalResult::Single(datetime) | LocalResult::Ambiguous(datetime, _) => Ok(datetime.into()),
+        LocalResult::None => bail!("Midnight of {date} does not exist in the local timezone"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(s: &str) -> DateTime<Utc> {
+        Local
+            .from_local_datetime(&NaiveDateTime::parse_from_str(s, "%F %T").unwrap())
+            .unwrap()
+            .into()
+    }
+
+    // a wednesday
+    fn now() -> DateTime<Utc> {
+        local("2024-01-17 15:30:00")
+    }
+
+    #[test]
+    fn points() {
+        let parse = |s| parse_time(now(), s).unwrap();
+        assert_eq!(parse("now"), now());
+        assert_eq!(parse("1h"), local("2024-01-17 14:30:00"));
+        assert_eq!(parse("today"), local("2024-01-17 00:00:00"));
+        assert_eq!(parse("yesterday"), local("2024-01-16 00:00:00"));
+        assert_eq!(parse("last monday"), local("2024-01-15 00:00:00"));
+        assert_eq!(parse("Wednesday"), local("2024-01-10 00:00:00"));
+        assert_eq!(parse("2024-01-01"), local("2024-01-01 00:00:00"));
+        assert_eq!(parse("2024-01-01 10:20"), local("2024-01-01 10:20:00"));
+        assert_eq!(parse("2024-01-01 10:20:30"), local("2024-01-01 10:20:30"));
+        assert_eq!(parse("2024-01-01T10:20"), local("2024-01-01 10:20:00"));
+        assert_eq!(
+            parse("2024-01-01T10:20:30Z"),
+            Utc.with_ymd_and_hms(2024, 1, 1, 10, 20, 30).unwrap()
+        );
+    }
+
+    #[test]
+    fn point_errors() {
+        for s in ["", "tomorrow", "last", "last week", "2024-13-01", "2024-01-01 25:00"] {
+            assert!(parse_time(now(), s).is_err(), "Expected `{s}` to fail");
+        }
+    }
+
+    #[test]
+    fn spans() {
+        let parse = |s| parse_time_span(now(), s).unwrap();
+        assert_eq!(
+            parse("2024-01-01..2024-01-02"),
+            (Some(local("2024-01-01 00:00:00")), Some(local("2024-01-02 00:00:00")))
+        );
+        assert_eq!(parse("yesterday.."), (Some(local("2024-01-16 00:00:00")), None));
+        assert_eq!(parse("..2d"), (None, Some(local("2024-01-15 15:30:00"))));
+        assert_eq!(
+            parse("yesterday"),
+            (Some(local("2024-01-16 00:00:00")), Some(local("2024-01-17 00:00:00")))
+        );
+        assert!(parse_time_span(now(), "2d").is_err());
+        assert!(parse_time_span(now(), "2030-01-01..2020-01-01").is_err());
+        assert!(parse_time_span(now(), "1d..2d").is_err());
+        assert!(parse_time_span(now(), "2020-01-01..2020-01-01").is_ok());
+    }
+}
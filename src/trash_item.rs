@@ -1,8 +1,14 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
 use either::Either;
 use trash::TrashItem;
 
 use crate::utils::swap;
 
+/// Trash items, which may have the indices they had in 'trashy list'.
 pub struct MaybeIndexedTrashItems(pub Either<Vec<TrashItem>, Vec<(u32, TrashItem)>>);
 
 impl MaybeIndexedTrashItems {
@@ -20,7 +26,98 @@ impl MaybeIndexedTrashItems {
             .into_iter()
     }
 
+    /// The items with their indices, where items without one are numbered from 0.
+    pub fn into_indexed_items(self) -> Vec<(u32, TrashItem)> {
+        self.0.either(|v| v.into_iter().zip(0..).map(swap).collect(), |v| v)
+    }
+
     pub fn len(&self) -> usize {
         self.0.as_ref().either(|v| v.len(), |v| v.len())
     }
 }
+
+/// The trash directory an item lives in, derived from its info file path.
+pub fn trash_dir(item: &TrashItem) -> Option<PathBuf> {
+    if cfg!(target_os = "linux") {
+        Some(Path::new(&item.id).parent()?.parent()?.to_path_buf())
+    } else {
+        None
+    }
+}
+
+/// The path of the `.trashinfo` file describing an item.
+pub fn info_path(item: &TrashItem) -> Option<PathBuf> {
+    if cfg!(target_os = "linux") {
+        Some(PathBuf::from(&item.id))
+    } else {
+        None
+    }
+}
+
+/// The path of the trashed file itself, inside the `files` directory of the trash.
+pub fn payload_path(item: &TrashItem) -> Option<PathBuf> {
+    if cfg!(target_os = "linux") {
+        Some(files_path_from_info_path(Path::new(&item.id)))
+    } else {
+        None
+    }
+}
+
+pub fn payload_metadata(item: &TrashItem) -> Option<fs::Metadata> {
+    fs::symlink_metadata(payload_path(item)?).ok()
+}
+
+/// The recursive size of the trashed file, not following symlinks.
+/// Directories themselves do not count towards the size, only their contents.
+pub fn payload_size(item: &TrashItem) -> Option<u64> {
+    dir_size(&payload_path(item)?).ok()
+}
+
+pub fn dir_size(path: &Path) -> io::Result<u64> {
+    let meta = fs::symlink_metadata(path)?;
+    if !meta.is_dir() {
+        return Ok(meta.len());
+    }
+    let mut size = 0;
+    for entry in fs::read_dir(path)? {
+        size += dir_size(&entry?.path())?;
+    }
+    Ok(size)
+}
+
+/// The number of files and directories inside of `path`, recursively.
+pub fn count_entries(path: &Path) -> io::Result<u64> {
+    let mut count = 0;
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        count += 1;
+        if entry.file_type()?.is_dir() {
+            count += count_entries(&entry.path())?;
+        }
+    }
+    Ok(count)
+}
+
+/// The sorted names of the entries of a directory, with a `/` after the names of directories.
+pub fn dir_entries(path: &Path) -> io::Result<Vec<String>> {
+    let mut entries = fs::read_dir(path)?
+        .map(|entry| {
+            let entry = entry?;
+            let mut name = entry.file_name().to_string_lossy().into_owned();
+            if entry.file_type()?.is_dir() {
+                name.push('/');
+            }
+            Ok(name)
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+    entries.sort();
+    Ok(entries)
+}
+
+pub fn files_path_from_info_path(info_path: &Path) -> PathBuf {
+    let file_name = Path::new(info_path.file_name().unwrap()).with_extension("");
+    let mut files_path =
+        (|| Some(info_path.parent()?.parent()?.join("files")))().expect("Invalid info_path");
+    files_path.push(file_name);
+    files_path
+}
@@ -1,5 +1,5 @@
-use std::fs;
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
+use std::{env, fs, io};
 
 use lscolors::{LsColors, Style};
 use once_cell::sync::Lazy;
@@ -14,8 +14,40 @@ pub mod path {
         path.as_os_str().to_string_lossy().to_string()
     }
 
+    /// Makes `path` absolute and removes `.` and `..` components without touching the file system.
+    pub fn normalize(path: &Path) -> io::Result<PathBuf> {
+        let path =
+            if path.is_absolute() { path.to_path_buf() } else { env::current_dir()?.join(path) };
+        let mut normalized = PathBuf::new();
+        for component in path.components() {
+            match component {
+                Component::CurDir => (),
+                Component::ParentDir => {
+                    normalized.pop();
+                }
+                component => normalized.push(component),
+            }
+        }
+        Ok(normalized)
+    }
+
     pub fn style_for<'a>(path: &Path, metadata: &'a fs::Metadata) -> Option<&'a Style> {
-        LS_COLORS.style_for_path_with_metadata(&path, Some(metadata))
+        LS_COLORS.style_for_path_with_metadata(path, Some(metadata))
+    }
+}
+
+pub fn display_size(bytes: u64) -> String {
+    const UNITS: [&str; 6] = ["B", "K", "M", "G", "T", "P"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes}B")
+    } else {
+        format!("{size:.1}{}", UNITS[unit])
     }
 }
 
@@ -31,3 +63,17 @@ pub fn clone_trash_item(item: &TrashItem) -> TrashItem {
 pub fn swap<T, U>((t, u): (T, U)) -> (U, T) {
     (u, t)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize() {
+        let normalize = |s| path::normalize(Path::new(s)).unwrap();
+        assert_eq!(normalize("/a/./b/../c"), PathBuf::from("/a/c"));
+        assert_eq!(normalize("/a/b/.."), PathBuf::from("/a"));
+        assert_eq!(normalize("/.."), PathBuf::from("/"));
+        assert_eq!(normalize("a/b"), env::current_dir().unwrap().join("a/b"));
+    }
+}